@@ -1,7 +1,7 @@
 from __future__ import __annotations__
 
 from enum import Enum
-from typing import TYPE_CHECKING, Optional
+from typing import TYPE_CHECKING, Optional, Union
 
 from imagetext_py.lib import Color, EmojiOptions
 
@@ -26,12 +26,117 @@ class Canvas:
         Args:
             path (str): The path to save the file to.
         """
+    def paste(
+        self,
+        other: Canvas,
+        x: int,
+        y: int,
+        opacity: float = 1.0,
+        blend_mode: BlendMode = BlendMode.Normal,
+    ) -> None:
+        """Composite another canvas onto this canvas.
+
+        The other canvas may be this canvas itself.
+
+        Args:
+            other (Canvas): The canvas to paste.
+            x (int): The x position of the other canvas, may be negative.
+            y (int): The y position of the other canvas, may be negative.
+            opacity (float, optional): The opacity of the other canvas. Defaults to 1.0.
+            blend_mode (BlendMode, optional): The blend mode. Defaults to BlendMode.Normal.
+        """
+    def fill_rect(self, x: float, y: float, width: float, height: float, paint: Paint) -> None:
+        """Fill a rectangle.
+
+        Args:
+            x (float): The x position of the rectangle.
+            y (float): The y position of the rectangle.
+            width (float): The width of the rectangle.
+            height (float): The height of the rectangle.
+            paint (Paint): The fill paint.
+        """
+    def fill_rounded_rect(
+        self, x: float, y: float, width: float, height: float, radius: float, paint: Paint
+    ) -> None:
+        """Fill a rectangle with rounded corners.
+
+        Args:
+            x (float): The x position of the rectangle.
+            y (float): The y position of the rectangle.
+            width (float): The width of the rectangle.
+            height (float): The height of the rectangle.
+            radius (float): The corner radius.
+            paint (Paint): The fill paint.
+        """
+    def fill_ellipse(self, cx: float, cy: float, rx: float, ry: float, paint: Paint) -> None:
+        """Fill an ellipse.
+
+        Args:
+            cx (float): The x position of the center.
+            cy (float): The y position of the center.
+            rx (float): The horizontal radius.
+            ry (float): The vertical radius.
+            paint (Paint): The fill paint.
+        """
+    def stroke_line(
+        self,
+        x0: float,
+        y0: float,
+        x1: float,
+        y1: float,
+        width: Union[float, Stroke],
+        paint: Paint,
+    ) -> None:
+        """Draw a line.
+
+        Args:
+            x0 (float): The x position of the start.
+            y0 (float): The y position of the start.
+            x1 (float): The x position of the end.
+            y1 (float): The y position of the end.
+            width (float | Stroke): The width of the line, or its stroke.
+            paint (Paint): The stroke paint.
+        """
+    def fill_path(self, path: Path, paint: Paint) -> None:
+        """Fill a path.
+
+        Args:
+            path (Path): The path.
+            paint (Paint): The fill paint.
+        """
+    def stroke_path(self, path: Path, width: Union[float, Stroke], paint: Paint) -> None:
+        """Stroke a path.
+
+        Args:
+            path (Path): The path.
+            width (float | Stroke): The width of the stroke, or the stroke.
+            paint (Paint): The stroke paint.
+        """
     def to_bytes(self) -> tuple[tuple[int, int], bytes]:
         """Get the canvas as bytes.
 
         Returns:
             tuple[tuple[int, int], bytes]: The canvas dimensions and bytes.
         """
+    def encode(
+        self,
+        format: str,
+        quality: Optional[int] = None,
+        compression: Optional[str] = None,
+    ) -> bytes:
+        """Encode the canvas into an image file in memory.
+
+        Supported formats are 'png', 'jpeg', 'webp', 'bmp' and 'tiff'.
+
+        Args:
+            format (str): The image format.
+            quality (int, optional): The quality (0-100) for jpeg and webp. Jpeg defaults to 75,
+                webp is encoded losslessly when no quality is given. Defaults to None.
+            compression (str, optional): The png compression, 'default', 'fast' or 'best'. Defaults to None.
+
+        Returns:
+            bytes: The encoded image.
+        """
     def to_image(self) -> Image.Image:
         """Get the canvas as a PIL image.
 
@@ -44,6 +149,53 @@ class Canvas:
         Returns:
             list[int]: The canvas as a buffer.
         """
+    def __buffer__(self, flags: int) -> memoryview:
+        """The canvas pixels as a writable HxWx4 buffer of bytes.
+
+        `memoryview(canvas)` and `numpy.asarray(canvas)` view the canvas pixels without copying them,
+        drawing on the canvas is visible through the view and writing to the view changes the canvas.
+        The canvas is kept alive for as long as the view.
+        """
+    @staticmethod
+    def from_array(array) -> Canvas:
+        """Create a canvas drawing onto a HxWx4 uint8 array, such as a numpy array.
+
+        The canvas shares the memory of the array, which it keeps alive, so drawing on the canvas
+        changes the array. Read-only arrays are copied into the canvas instead.
+
+        Args:
+            array: The array, any C-contiguous object supporting the buffer protocol.
+
+        Raises:
+            ValueError: If the array isn't a C-contiguous HxWx4 array of bytes.
+
+        Returns:
+            Canvas: The canvas.
+        """
+    @staticmethod
+    def open(path: str) -> Canvas:
+        """Open an image file as a canvas.
+
+        Supports png, jpeg, webp, gif, bmp and tiff files, the image is converted to RGBA.
+
+        Args:
+            path (str): The path to the image.
+
+        Returns:
+            Canvas: The canvas.
+        """
+    @staticmethod
+    def from_encoded(data: bytes) -> Canvas:
+        """Create a canvas from an encoded image, such as the contents of a png file.
+
+        Supports png, jpeg, webp, gif, bmp and tiff data, the image is converted to RGBA.
+
+        Args:
+            data (bytes): The encoded image.
+
+        Returns:
+            Canvas: The canvas.
+        """
     @staticmethod
     def from_image(image: Image.Image) -> Canvas:
         """Create a canvas from an image.
@@ -116,38 +268,392 @@ class Paint:
             Paint: The paint.
         """
 
+class Path:
+    def __new__(cls) -> Path:
+        """Create a new empty path.
+
+        Returns:
+            Path: The path.
+        """
+    def move_to(self, x: float, y: float) -> None:
+        """Start a new contour at a point.
+
+        Args:
+            x (float): The x position.
+            y (float): The y position.
+        """
+    def line_to(self, x: float, y: float) -> None:
+        """Add a line to a point.
+
+        Args:
+            x (float): The x position.
+            y (float): The y position.
+        """
+    def quad_to(self, x1: float, y1: float, x: float, y: float) -> None:
+        """Add a quadratic curve to a point.
+
+        Args:
+            x1 (float): The x position of the control point.
+            y1 (float): The y position of the control point.
+            x (float): The x position.
+            y (float): The y position.
+        """
+    def cubic_to(
+        self, x1: float, y1: float, x2: float, y2: float, x: float, y: float
+    ) -> None:
+        """Add a cubic curve to a point.
+
+        Args:
+            x1 (float): The x position of the first control point.
+            y1 (float): The y position of the first control point.
+            x2 (float): The x position of the second control point.
+            y2 (float): The y position of the second control point.
+            x (float): The x position.
+            y (float): The y position.
+        """
+    def close(self) -> None:
+        """Close the current contour."""
+
+class Stroke:
+    def __new__(
+        cls,
+        width: float,
+        join: LineJoin = LineJoin.Miter,
+        cap: LineCap = LineCap.Butt,
+        miter_limit: float = 4.0,
+        dash: Optional[list[float]] = None,
+        dash_offset: float = 0.0,
+        placement: StrokePlacement = StrokePlacement.Center,
+    ) -> Stroke:
+        """Create a new stroke.
+
+        Args:
+            width (float): The width of the stroke.
+            join (LineJoin, optional): How corners are joined. Defaults to LineJoin.Miter.
+            cap (LineCap, optional): How open ends are capped. Defaults to LineCap.Butt.
+            miter_limit (float, optional): The limit past which miter joins are beveled. Defaults to 4.0.
+            dash (list[float], optional): Alternating dash and gap lengths, an even number of positive lengths. Defaults to None.
+            dash_offset (float, optional): The offset into the dash pattern to start at. Defaults to 0.0.
+            placement (StrokePlacement, optional): Where the stroke sits relative to the outline. Defaults to StrokePlacement.Center.
+
+        Returns:
+            Stroke: The stroke.
+        """
+    @property
+    def width(self) -> float:
+        """The width of the stroke."""
+    @property
+    def miter_limit(self) -> float:
+        """The limit past which miter joins are beveled."""
+    @property
+    def placement(self) -> StrokePlacement:
+        """Where the stroke sits relative to the outline."""
+
+class Span:
+    def __new__(
+        cls,
+        text: str,
+        font: Font,
+        size: float,
+        fill: Paint,
+        stroke: Optional[Union[float, Stroke]] = None,
+        stroke_color: Optional[Paint] = None,
+    ) -> Span:
+        """Create a new span, a run of rich text sharing one style.
+
+        Args:
+            text (str): The text of the span.
+            font (Font): The font of the span.
+            size (float): The size of the span.
+            fill (Paint): The fill of the span.
+            stroke (float | Stroke, optional): The stroke width of the span, or its stroke. Defaults to None.
+            stroke_color (Paint, optional): The stroke color of the span. Defaults to None.
+
+        Returns:
+            Span: The span.
+        """
+    @property
+    def text(self) -> str:
+        """The text of the span."""
+    @property
+    def size(self) -> float:
+        """The size of the span."""
+
+class Markup:
+    def __new__(
+        cls,
+        bold: Optional[str] = None,
+        italic: Optional[str] = None,
+        bold_italic: Optional[str] = None,
+    ) -> Markup:
+        """Create a new markup parser for BBCode-like tags.
+
+        Supported tags are [b], [i], [font=NAME], [size=SIZE], [color=#RRGGBB], [stroke=WIDTH]
+        and [stroke_color=#RRGGBB], colors may also be given as #RGB, #RGBA or #RRGGBBAA.
+        Every tag must be closed, and "[[" is a literal "[".
+
+        Args:
+            bold (str, optional): The FontDB query of the font used by [b]. Defaults to None.
+            italic (str, optional): The FontDB query of the font used by [i]. Defaults to None.
+            bold_italic (str, optional): The FontDB query of the font used by [b][i]. Defaults to None.
+
+        Returns:
+            Markup: The markup parser.
+        """
+    def parse(
+        self,
+        text: str,
+        font: Font,
+        size: float,
+        fill: Paint,
+        stroke: Optional[Union[float, Stroke]] = None,
+        stroke_color: Optional[Paint] = None,
+    ) -> list[Span]:
+        """Parse markup into spans, styled by the given style outside of any tags.
+
+        Args:
+            text (str): The markup.
+            font (Font): The font of untagged text.
+            size (float): The size of untagged text.
+            fill (Paint): The fill of untagged text.
+            stroke (float | Stroke, optional): The stroke of untagged text. Defaults to None.
+            stroke_color (Paint, optional): The stroke color of untagged text. Defaults to None.
+
+        Raises:
+            MarkupError: If the markup is malformed or a font can't be found.
+
+        Returns:
+            list[Span]: The spans.
+        """
+
+class MarkupError(ValueError): ...
+
+class Shadow:
+    def __new__(
+        cls,
+        offset: tuple[float, float] = (0.0, 0.0),
+        blur: float = 0.0,
+        color: Optional[Paint] = None,
+    ) -> Shadow:
+        """Create a new shadow, drawn beneath the text from its glyphs, outline and emojis.
+
+        Args:
+            offset (tuple[float, float], optional): The offset of the shadow from the text. Defaults to (0.0, 0.0).
+            blur (float, optional): The blur radius of the shadow. Defaults to 0.0.
+            color (Paint, optional): The paint of the shadow. Defaults to black.
+
+        Returns:
+            Shadow: The shadow.
+        """
+    @property
+    def offset(self) -> tuple[float, float]:
+        """The offset of the shadow from the text."""
+    @property
+    def blur(self) -> float:
+        """The blur radius of the shadow."""
+
+class Decoration:
+    def __new__(
+        cls,
+        line: DecorationLine,
+        color: Optional[Paint] = None,
+        thickness: Optional[float] = None,
+        skip_ink: bool = False,
+    ) -> Decoration:
+        """Create a new decoration, a line drawn under, through or over the text.
+
+        The position and thickness of underlines and strikethroughs come from the font.
+
+        Args:
+            line (DecorationLine): Where the line is drawn.
+            color (Paint, optional): The paint of the line. Defaults to the fill of the text.
+            thickness (float, optional): The thickness of the line in pixels. Defaults to the font's.
+            skip_ink (bool, optional): Whether the line leaves gaps where it crosses glyphs. Defaults to False.
+
+        Returns:
+            Decoration: The decoration.
+        """
+    @property
+    def line(self) -> DecorationLine:
+        """Where the line is drawn."""
+    @property
+    def thickness(self) -> Optional[float]:
+        """The thickness of the line, None when it comes from the font."""
+    @property
+    def skip_ink(self) -> bool:
+        """Whether the line leaves gaps where it crosses glyphs."""
+
 class Font:
     def __new__(
         cls,
         path: str,
-        fallbacks: Optional[list[str]] = None,
+        fallbacks: Optional[list[Union[str, bytes, Font]]] = None,
         emoji_options: Optional[EmojiOptions] = None,
+        features: Optional[dict[str, int]] = None,
+        variations: Optional[dict[str, float]] = None,
+        index: int = 0,
     ) -> Font:
         """Create a new font.
 
         Args:
             path (str): The path to the font.
-            fallbacks (list[str], optional): The fallback fonts. Defaults to None.
+            fallbacks (list[str | bytes | Font], optional): The fallback fonts, as paths, font data or loaded fonts, which share their data. Defaults to None.
+            emoji_options (EmojiOptions, optional): The emoji options. Defaults to the default emoji options.
+            features (dict[str, int], optional): OpenType features text drawn and measured with the font gets by default, like {"tnum": 1, "liga": 0}. Text with features is shaped. Defaults to None.
+            variations (dict[str, float], optional): Values for the axes of a variable font, like {"wght": 650, "wdth": 85}, applied to its outlines and advances. Fallback fonts take the axes they have. Defaults to None.
+            index (int, optional): The face to load from a font collection (.ttc, .otc). Defaults to 0.
+
+        Returns:
+            Font: The font.
+        """
+    @staticmethod
+    def from_bytes(
+        data: bytes,
+        index: int = 0,
+        fallbacks: Optional[list[Union[str, bytes, Font]]] = None,
+        emoji_options: Optional[EmojiOptions] = None,
+        features: Optional[dict[str, int]] = None,
+        variations: Optional[dict[str, float]] = None,
+    ) -> Font:
+        """Create a new font from the contents of a font file.
+
+        Args:
+            data (bytes): The font data.
+            index (int, optional): The face to load from a font collection (.ttc, .otc). Defaults to 0.
+            fallbacks (list[str | bytes | Font], optional): The fallback fonts, as paths, font data or loaded fonts, which share their data. Defaults to None.
             emoji_options (EmojiOptions, optional): The emoji options. Defaults to the default emoji options.
+            features (dict[str, int], optional): OpenType features text drawn and measured with the font gets by default, like {"tnum": 1, "liga": 0}. Text with features is shaped. Defaults to None.
+            variations (dict[str, float], optional): Values for the axes of a variable font, like {"wght": 650, "wdth": 85}, applied to its outlines and advances. Fallback fonts take the axes they have. Defaults to None.
 
         Returns:
             Font: The font.
         """
+    @staticmethod
+    def faces(source: Union[str, bytes]) -> list[tuple[int, Optional[str], Optional[str]]]:
+        """List the faces of a font file. Font collections have several, other fonts a single one.
+
+        Args:
+            source (str | bytes): The path to the font, or its data.
+
+        Returns:
+            list[tuple[int, str | None, str | None]]: The index, family name and style name of each face.
+        """
     def set_emoji_options(self, emoji_options: EmojiOptions) -> None:
         """Set the emoji options of the font.
 
         Args:
             emoji_options (EmojiOptions): The emoji options.
         """
+    @property
+    def fallbacks(self) -> list[Font]:
+        """The fallback fonts, in the order they're tried, each without fallbacks of its own."""
+    def add_fallback(self, font: Union[str, bytes, Font]) -> None:
+        """Add a fallback font, tried after the others. It's drawn at the font's variations, for the axes it has.
+
+        Args:
+            font (str | bytes | Font): The path to the font, its data, or a loaded font, whose data is shared. Loaded fonts are added without their fallbacks.
+        """
+    def insert_fallback(self, index: int, font: Union[str, bytes, Font]) -> None:
+        """Insert a fallback font before the one at an index, like list.insert. It's drawn at the font's variations, for the axes it has.
+
+        Args:
+            index (int): Where to insert the font in the fallbacks. Negative indices count from the end.
+            font (str | bytes | Font): The path to the font, its data, or a loaded font, whose data is shared. Loaded fonts are added without their fallbacks.
+        """
+    def remove_fallback(self, index: int) -> None:
+        """Remove the fallback font at an index, like list.pop.
+
+        Args:
+            index (int): The index of the fallback. Negative indices count from the end.
+
+        Raises:
+            IndexError: If there is no fallback at the index.
+        """
+    def with_fallbacks(self, fallbacks: list[Union[str, bytes, Font]]) -> Font:
+        """Copy the font with other fallbacks, sharing the font's data instead of loading it again. The fallbacks are drawn at the font's variations, for the axes they have.
+
+        Args:
+            fallbacks (list[str | bytes | Font]): The fallback fonts, as paths, font data or loaded fonts, which share their data.
+
+        Returns:
+            Font: The font with the fallbacks.
+        """
+    @property
+    def family(self) -> Optional[str]:
+        """The family name of the main font."""
+    @property
+    def subfamily(self) -> Optional[str]:
+        """The style name of the main font, like "Bold Italic"."""
+    @property
+    def units_per_em(self) -> int:
+        """The font units in an em of the main font, which the other metrics are given in."""
+    @property
+    def ascent(self) -> int:
+        """How far the main font rises above the baseline, in font units."""
+    @property
+    def descent(self) -> int:
+        """How far the main font falls below the baseline, in font units. Negative below the baseline."""
+    @property
+    def line_gap(self) -> int:
+        """The gap the main font puts between lines, in font units."""
+    @property
+    def cap_height(self) -> Optional[int]:
+        """The height of the main font's capital letters, in font units."""
+    @property
+    def x_height(self) -> Optional[int]:
+        """The height of the main font's lowercase letters, in font units."""
+    @property
+    def underline_position(self) -> Optional[int]:
+        """Where the main font places the top of underlines, in font units. Negative below the baseline."""
+    @property
+    def underline_thickness(self) -> Optional[int]:
+        """How thick the main font draws underlines, in font units."""
+    def has_glyph(self, ch: str) -> bool:
+        """Check whether the font or any of its fallbacks has a glyph for a character.
+
+        Args:
+            ch (str): The character.
+
+        Returns:
+            bool: Whether the character can be drawn.
+        """
+    def missing_chars(self, text: str) -> list[str]:
+        """Find the characters of some text neither the font nor its fallbacks have glyphs for.
+
+        Args:
+            text (str): The text.
+
+        Returns:
+            list[str]: Each missing character once, in the order they first appear.
+        """
+    def resolve(self, text: str) -> list[tuple[str, Optional[int]]]:
+        """Find the font each character of some text is drawn with.
+
+        Args:
+            text (str): The text.
+
+        Returns:
+            list[tuple[str, int | None]]: Each character with the index of its font in the fallback chain, 0 being the font itself and 1 its first fallback, or None when no font has it.
+        """
 
 class FontDB:
     @staticmethod
-    def LoadFromPath(name: str, path: str) -> None:
+    def LoadFromPath(name: str, path: str, index: int = 0) -> None:
         """Load a font from a path. The font will be available by name.
 
         Args:
             name (str): The inputted name of the font.
             path (str): The path to the font.
+            index (int, optional): The face to load from a font collection (.ttc, .otc). Defaults to 0.
+        """
+    @staticmethod
+    def LoadFromBytes(name: str, data: bytes, index: int = 0) -> None:
+        """Load a font from its data. The font will be available by name.
+
+        Args:
+            name (str): The inputted name of the font.
+            data (bytes): The font data.
+            index (int, optional): The face to load from a font collection (.ttc, .otc). Defaults to 0.
         """
     @staticmethod
     def LoadFromDir(path: str) -> None:
@@ -160,19 +666,24 @@ class FontDB:
     def LoadSystemFonts() -> None:
         """Load all system found fonts."""
     @staticmethod
-    def Query(names: str) -> Font:
+    def Query(names: str, variations: Optional[dict[str, float]] = None) -> Font:
         """Query a font by names. ex. 'Segoe-UI Segoe-UI-Emoji Segoe-UI-Symbol'
 
         A font with fallbacks and using default emoji options will be returned.
 
         Args:
             names (str): The name of the font.
+            variations (dict[str, float], optional): Values for the axes of a variable font, like {"wght": 650, "wdth": 85}, applied to its outlines and advances. Fallback fonts take the axes they have. Defaults to None.
 
         Returns:
             Font: The font.
         """
     @staticmethod
-    def QueryWithEmoji(names: str, emoji_options: EmojiOptions) -> Font:
+    def QueryWithEmoji(
+        names: str,
+        emoji_options: EmojiOptions,
+        variations: Optional[dict[str, float]] = None,
+    ) -> Font:
         """Query a font by names. ex. 'Segoe-UI Segoe-UI-Emoji Segoe-UI-Symbol'
 
         A font with fallbacks will be returned.
@@ -180,13 +691,14 @@ class FontDB:
         Args:
             names (str): The name of the font.
             emoji_options (EmojiOptions): The emoji options.
+            variations (dict[str, float], optional): Values for the axes of a variable font, like {"wght": 650, "wdth": 85}, applied to its outlines and advances. Fallback fonts take the axes they have. Defaults to None.
 
         Returns:
             Font: The font.
         """
     @staticmethod
     def Remove(name: str) -> None:
-        """Remove a font from the database.
+        """Remove a font from the database, whether it was loaded from a path or from bytes.
 
         Args:
             name (str): The name of the font.
@@ -198,6 +710,20 @@ class FontDB:
         Args:
             emoji_options (EmojiOptions): The emoji options.
         """
+    @staticmethod
+    def SetAutoFallbacks(enabled: bool) -> None:
+        """Find fallbacks in the database for characters no font of a chain has glyphs for.
+
+        When enabled, drawing and measuring text looks through the fonts loaded into the database
+        (by `LoadFromPath`, `LoadFromBytes`, `LoadFromDir` and `LoadSystemFonts`) for one covering each
+        missing character. Fonts covering more of the character's script are preferred, then fonts
+        closest in weight and slant to the main font. The picks are cached per character. Fonts are
+        indexed as they're loaded, or when this is enabled for those loaded before, and a picked
+        font file is only read in full once a character is missing. Disabled by default.
+
+        Args:
+            enabled (bool): Whether to find fallbacks.
+        """
 
 class EmojiSource:
     @staticmethod
@@ -321,11 +847,63 @@ class TextAlign(Enum):
     Left = 0
     Center = 1
     Right = 2
+    Justify = 3
+    JustifyAll = 4
+    Start = 5
+    End = 6
 
 class WrapStyle(Enum):
     Word = 0
     Character = 1
 
+class BlendMode(Enum):
+    Normal = 0
+    Multiply = 1
+    Screen = 2
+    Overlay = 3
+    Darken = 4
+    Lighten = 5
+    ColorDodge = 6
+    ColorBurn = 7
+    HardLight = 8
+    SoftLight = 9
+    Difference = 10
+    Exclusion = 11
+
+class LineJoin(Enum):
+    Miter = 0
+    Round = 1
+    Bevel = 2
+
+class LineCap(Enum):
+    Butt = 0
+    Round = 1
+    Square = 2
+
+class StrokePlacement(Enum):
+    Outside = 0
+    Center = 1
+    Inside = 2
+
+class Overflow(Enum):
+    Clip = 0
+    Ellipsis = 1
+    Fade = 2
+
+class DecorationLine(Enum):
+    Underline = 0
+    Strikethrough = 1
+    Overline = 2
+
+class BackgroundMode(Enum):
+    Line = 0
+    Block = 1
+
+class Direction(Enum):
+    Auto = 0
+    Ltr = 1
+    Rtl = 2
+
 def draw_text(
     canvas: Canvas,
     text: str,
@@ -334,9 +912,19 @@ def draw_text(
     size: float,
     font: Font,
     fill: Paint,
-    stroke: Optional[float] = None,
+    stroke: Optional[Union[float, Stroke]] = None,
     stroke_color: Optional[Paint] = None,
     draw_emojis: bool = False,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    shaping: bool = False,
+    features: Optional[dict[str, int]] = None,
 ) -> None:
     """Draw text on a canvas.
 
@@ -348,9 +936,19 @@ def draw_text(
         size (float): The size of the text.
         font (Font): The font of the text.
         fill (Paint): The fill of the text.
-        stroke (float, optional): The stroke of the text. Defaults to None.
+        stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
         stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
     """
 
 def draw_text_anchored(
@@ -363,9 +961,19 @@ def draw_text_anchored(
     size: float,
     font: Font,
     fill: Paint,
-    stroke: Optional[float] = None,
+    stroke: Optional[Union[float, Stroke]] = None,
     stroke_color: Optional[Paint] = None,
     draw_emojis: bool = False,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    shaping: bool = False,
+    features: Optional[dict[str, int]] = None,
 ) -> None:
     """Draw text on a canvas.
 
@@ -379,9 +987,19 @@ def draw_text_anchored(
         size (float): The size of the text.
         font (Font): The font of the text.
         fill (Paint): The fill of the text.
-        stroke (float, optional): The stroke of the text. Defaults to None.
+        stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
         stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
     """
 
 def draw_text_multiline(
@@ -397,9 +1015,26 @@ def draw_text_multiline(
     fill: Paint,
     line_spacing: float = 1.0,
     align: TextAlign = TextAlign.Left,
-    stroke: Optional[float] = None,
+    stroke: Optional[Union[float, Stroke]] = None,
     stroke_color: Optional[Paint] = None,
     draw_emojis: bool = False,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    markup: Optional[Markup] = None,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    background: Optional[Paint] = None,
+    padding: Union[float, tuple[float, float]] = 0.0,
+    corner_radius: float = 0.0,
+    background_mode: BackgroundMode = BackgroundMode.Line,
+    highlights: Optional[list[tuple[int, int, Paint]]] = None,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
 ) -> None:
     """Draw text on a canvas.
 
@@ -416,9 +1051,26 @@ def draw_text_multiline(
         fill (Paint): The fill of the text.
         line_spacing (float, optional): The line spacing. Defaults to 1.0.
         align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
-        stroke (float, optional): The stroke of the text. Defaults to None.
+        stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
         stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        markup (Markup, optional): Parse the text as markup with this parser. Defaults to None.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        background (Paint, optional): The paint of a box drawn behind the text. Defaults to None.
+        padding (float | tuple[float, float], optional): The padding of the background box, or its (horizontal, vertical) padding. Defaults to 0.0.
+        corner_radius (float, optional): The corner radius of the background box. Defaults to 0.0.
+        background_mode (BackgroundMode, optional): Whether each line gets its own background box or the whole text shares one. Defaults to BackgroundMode.Line.
+        highlights (list[tuple[int, int, Paint]], optional): Ranges of characters (start, end) of the lines joined by newlines, without any markup tags, painted behind their glyphs. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
     """
 
 def draw_text_wrapped(
@@ -434,10 +1086,30 @@ def draw_text_wrapped(
     fill: Paint,
     line_spacing: float = 1.0,
     align: TextAlign = TextAlign.Left,
-    stroke: Optional[float] = None,
+    stroke: Optional[Union[float, Stroke]] = None,
     stroke_color: Optional[Paint] = None,
     draw_emojis: bool = False,
     wrap_style: WrapStyle = WrapStyle.Word,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    markup: Optional[Markup] = None,
+    max_lines: Optional[int] = None,
+    overflow: Overflow = Overflow.Clip,
+    ellipsis: str = "…",
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    background: Optional[Paint] = None,
+    padding: Union[float, tuple[float, float]] = 0.0,
+    corner_radius: float = 0.0,
+    background_mode: BackgroundMode = BackgroundMode.Line,
+    highlights: Optional[list[tuple[int, int, Paint]]] = None,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
 ) -> None:
     """Draw text on a canvas.
 
@@ -454,10 +1126,149 @@ def draw_text_wrapped(
         fill (Paint): The fill of the text.
         line_spacing (float, optional): The line spacing. Defaults to 1.0.
         align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
-        stroke (float, optional): The stroke of the text. Defaults to None.
+        stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
         stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
         wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        markup (Markup, optional): Parse the text as markup with this parser. Defaults to None.
+        max_lines (int, optional): The most lines to draw. Defaults to None.
+        overflow (Overflow, optional): How the last line ends when there are more than max_lines. Defaults to Overflow.Clip.
+        ellipsis (str, optional): The text ending the last line with Overflow.Ellipsis. Defaults to "…".
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        background (Paint, optional): The paint of a box drawn behind the text. Defaults to None.
+        padding (float | tuple[float, float], optional): The padding of the background box, or its (horizontal, vertical) padding. Defaults to 0.0.
+        corner_radius (float, optional): The corner radius of the background box. Defaults to 0.0.
+        background_mode (BackgroundMode, optional): Whether each line gets its own background box or the whole text shares one. Defaults to BackgroundMode.Line.
+        highlights (list[tuple[int, int, Paint]], optional): Ranges of characters (start, end) of the text, without any markup tags, painted behind their glyphs. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+    """
+
+def draw_text_fitted(
+    canvas: Canvas,
+    text: str,
+    x: float,
+    y: float,
+    ax: float,
+    ay: float,
+    width: float,
+    height: float,
+    font: Font,
+    fill: Paint,
+    min_size: float = 1.0,
+    max_size: Optional[float] = None,
+    line_spacing: float = 1.0,
+    align: TextAlign = TextAlign.Left,
+    stroke: Optional[Union[float, Stroke]] = None,
+    stroke_color: Optional[Paint] = None,
+    draw_emojis: bool = False,
+    wrap_style: WrapStyle = WrapStyle.Word,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
+) -> float:
+    """Draw text wrapped at the largest size that fits a box.
+
+    Args:
+        canvas (Canvas): The canvas.
+        text (str): The text to draw.
+        x (float): The x position of the text.
+        y (float): The y position of the text.
+        ax (float): The x anchor of the text.
+        ay (float): The y anchor of the text.
+        width (float): The width of the box.
+        height (float): The height of the box.
+        font (Font): The font of the text.
+        fill (Paint): The fill of the text.
+        min_size (float, optional): The smallest size to try, used when no size fits. Defaults to 1.0.
+        max_size (float, optional): The largest size to try. Defaults to the height of the box.
+        line_spacing (float, optional): The line spacing. Defaults to 1.0.
+        align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
+        stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
+        stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
+        draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+
+    Returns:
+        float: The size the text was drawn at.
+    """
+
+def draw_rich_text(
+    canvas: Canvas,
+    spans: list[Span],
+    x: float,
+    y: float,
+    ax: float,
+    ay: float,
+    width: Optional[float] = None,
+    line_spacing: float = 1.0,
+    align: TextAlign = TextAlign.Left,
+    draw_emojis: bool = False,
+    wrap_style: WrapStyle = WrapStyle.Word,
+    rotation: Optional[float] = None,
+    transform: Optional[tuple[float, float, float, float, float, float]] = None,
+    shadow: Optional[Shadow] = None,
+    glow: Optional[Shadow] = None,
+    outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    decorations: Optional[list[Decoration]] = None,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
+) -> None:
+    """Draw spans of rich text on a canvas as one paragraph.
+
+    Args:
+        canvas (Canvas): The canvas.
+        spans (list[Span]): The spans to draw, in order.
+        x (float): The x position of the text.
+        y (float): The y position of the text.
+        ax (float): The x anchor of the text.
+        ay (float): The y anchor of the text.
+        width (float, optional): The width to wrap the text to. Defaults to None.
+        line_spacing (float, optional): The line spacing, relative to the largest span on each line. Defaults to 1.0.
+        align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
+        draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+        transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+        shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+        glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+        outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
     """
 
 def text_size(
@@ -465,6 +1276,11 @@ def text_size(
     size: float,
     font: Font,
     draw_emojis: bool = False,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
 ) -> tuple[int, int]:
     """Get the size of a text in pixels.
 
@@ -473,6 +1289,11 @@ def text_size(
         size (float): The size of the text.
         font (Font): The font of the text.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
 
     Returns:
         tuple[float, float]: The size of the text.
@@ -484,6 +1305,11 @@ def text_size_multiline(
     font: Font,
     line_spacing: float = 1.0,
     draw_emojis: bool = False,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
 ) -> tuple[int, int]:
     """Get the size of a text in pixels.
 
@@ -493,11 +1319,80 @@ def text_size_multiline(
         font (Font): The font of the text.
         line_spacing (float, optional): The line spacing. Defaults to 1.0.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
 
     Returns:
         tuple[float, float]: The size of the text.
     """
 
+def text_lines_extents(
+    lines: list[str],
+    size: float,
+    font: Font,
+    width: Optional[float] = None,
+    line_spacing: float = 1.0,
+    align: TextAlign = TextAlign.Left,
+    draw_emojis: bool = False,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
+) -> list[tuple[float, float, float, float]]:
+    """Get where each line of a text is placed, as it is drawn by the multiline and wrapped functions.
+
+    Args:
+        lines (list[str]): The lines of the text.
+        size (float): The size of the text.
+        font (Font): The font of the text.
+        width (float, optional): The width to align the lines within. Defaults to the widest line.
+        line_spacing (float, optional): The line spacing. Defaults to 1.0.
+        align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
+        draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+
+    Returns:
+        list[tuple[float, float, float, float]]: The x, y, width and height of each line, relative to the top left of the text.
+    """
+
+def rich_text_size(
+    spans: list[Span],
+    width: Optional[float] = None,
+    line_spacing: float = 1.0,
+    draw_emojis: bool = False,
+    wrap_style: WrapStyle = WrapStyle.Word,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
+) -> tuple[int, int]:
+    """Get the size of spans of rich text in pixels.
+
+    Args:
+        spans (list[Span]): The spans.
+        width (float, optional): The width to wrap the text to. Defaults to None.
+        line_spacing (float, optional): The line spacing, relative to the largest span on each line. Defaults to 1.0.
+        draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+
+    Returns:
+        tuple[int, int]: The size of the text.
+    """
+
 def text_wrap(
     text: str,
     width: float,
@@ -505,6 +1400,14 @@ def text_wrap(
     font: Font,
     draw_emojis: bool = False,
     wrap_style: WrapStyle = WrapStyle.Word,
+    max_lines: Optional[int] = None,
+    overflow: Overflow = Overflow.Clip,
+    ellipsis: str = "…",
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
 ) -> list[str]:
     """Wrap a text on a given pixel width.
 
@@ -515,11 +1418,57 @@ def text_wrap(
         font (Font): The font of the text.
         draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
         wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        max_lines (int, optional): The most lines to return. Defaults to None.
+        overflow (Overflow, optional): How the last line ends when there are more than max_lines. Defaults to Overflow.Clip.
+        ellipsis (str, optional): The text ending the last line with Overflow.Ellipsis. Defaults to "…".
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
 
     Returns:
         list[str]: The wrapped text.
     """
 
+def fit_text(
+    text: str,
+    font: Font,
+    box_width: float,
+    box_height: float,
+    min_size: float = 1.0,
+    max_size: Optional[float] = None,
+    line_spacing: float = 1.0,
+    wrap_style: WrapStyle = WrapStyle.Word,
+    draw_emojis: bool = False,
+    letter_spacing: float = 0.0,
+    word_spacing: float = 0.0,
+    shaping: bool = False,
+    direction: Direction = Direction.Auto,
+    features: Optional[dict[str, int]] = None,
+) -> tuple[float, list[str]]:
+    """Find the largest size at which a text wrapped to a box fits it.
+
+    Args:
+        text (str): The text to fit.
+        font (Font): The font of the text.
+        box_width (float): The width of the box.
+        box_height (float): The height of the box.
+        min_size (float, optional): The smallest size to try, returned when no size fits. Defaults to 1.0.
+        max_size (float, optional): The largest size to try. Defaults to the height of the box.
+        line_spacing (float, optional): The line spacing. Defaults to 1.0.
+        wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+        draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+        letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+        word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+        shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+        direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Defaults to Direction.Auto.
+        features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+
+    Returns:
+        tuple[float, list[str]]: The size and the text wrapped at that size.
+    """
+
 def prebuild_static_vars() -> None:
     """Prebuild static variables.
 
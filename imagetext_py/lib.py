@@ -1,6 +1,6 @@
 from __future__ import annotations
 
-from typing import TYPE_CHECKING, Optional
+from typing import TYPE_CHECKING, Optional, Union
 
 from imagetext_py.imagetext_py import *
 
@@ -115,9 +115,19 @@ class Writer:
         size: float,
         font: Font,
         fill: Paint,
-        stroke: Optional[float] = None,
+        stroke: Optional[Union[float, Stroke]] = None,
         stroke_color: Optional[Paint] = None,
         draw_emojis: bool = False,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        shaping: bool = False,
+        features: Optional[dict[str, int]] = None,
     ) -> None:
         """Draw text on the image.
 
@@ -128,9 +138,19 @@ class Writer:
             size (float): The size of the text.
             font (Font): The font to use.
             fill (Paint): The fill paint.
-            stroke (float, optional): The stroke of the text. Defaults to None.
+            stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
             stroke_color (Paint, optional): The stroke color. Defaults to None.
             draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
         """
         draw_text(
             self._canvas,
@@ -143,6 +163,16 @@ class Writer:
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            shaping,
+            features,
         )
 
     def draw_text_anchored(
@@ -155,9 +185,19 @@ class Writer:
         size: float,
         font: Font,
         fill: Paint,
-        stroke: Optional[float] = None,
+        stroke: Optional[Union[float, Stroke]] = None,
         stroke_color: Optional[Paint] = None,
         draw_emojis: bool = False,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        shaping: bool = False,
+        features: Optional[dict[str, int]] = None,
     ) -> None:
         """Draw text on the image.
 
@@ -170,9 +210,19 @@ class Writer:
             size (float): The size of the text.
             font (Font): The font to use.
             fill (Paint): The fill paint.
-            stroke (float, optional): The stroke of the text. Defaults to None.
+            stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
             stroke_color (Paint, optional): The stroke color. Defaults to None.
             draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
         """
         draw_text_anchored(
             self._canvas,
@@ -187,6 +237,16 @@ class Writer:
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            shaping,
+            features,
         )
 
     def draw_text_multiline(
@@ -202,9 +262,26 @@ class Writer:
         fill: Paint,
         line_spacing: float = 1.0,
         align: TextAlign = TextAlign.Left,
-        stroke: Optional[float] = None,
+        stroke: Optional[Union[float, Stroke]] = None,
         stroke_color: Optional[Paint] = None,
         draw_emojis: bool = False,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        markup: Optional[Markup] = None,
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        background: Optional[Paint] = None,
+        padding: Union[float, tuple[float, float]] = 0.0,
+        corner_radius: float = 0.0,
+        background_mode: BackgroundMode = BackgroundMode.Line,
+        highlights: Optional[list[tuple[int, int, Paint]]] = None,
+        shaping: bool = False,
+        direction: Direction = Direction.Auto,
+        features: Optional[dict[str, int]] = None,
     ) -> None:
         """Draw text on the image.
 
@@ -220,9 +297,26 @@ class Writer:
             fill (Paint): The fill paint.
             line_spacing (float, optional): The line spacing. Defaults to 1.0.
             align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
-            stroke (float, optional): The stroke of the text. Defaults to None.
+            stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
             stroke_color (Paint, optional): The stroke color. Defaults to None.
             draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            markup (Markup, optional): Parse the text as markup with this parser. Defaults to None.
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            background (Paint, optional): The paint of a box drawn behind the text. Defaults to None.
+            padding (float | tuple[float, float], optional): The padding of the background box, or its (horizontal, vertical) padding. Defaults to 0.0.
+            corner_radius (float, optional): The corner radius of the background box. Defaults to 0.0.
+            background_mode (BackgroundMode, optional): Whether each line gets its own background box or the whole text shares one. Defaults to BackgroundMode.Line.
+            highlights (list[tuple[int, int, Paint]], optional): Ranges of characters (start, end) of the lines joined by newlines, without any markup tags, painted behind their glyphs. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
         """
         draw_text_multiline(
             self._canvas,
@@ -240,6 +334,23 @@ class Writer:
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            markup,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            background,
+            padding,
+            corner_radius,
+            background_mode,
+            highlights,
+            shaping,
+            direction,
+            features,
         )
 
     def draw_text_wrapped(
@@ -255,10 +366,30 @@ class Writer:
         fill: Paint,
         line_spacing: float = 1.0,
         align: TextAlign = TextAlign.Left,
-        stroke: Optional[float] = None,
+        stroke: Optional[Union[float, Stroke]] = None,
         stroke_color: Optional[Paint] = None,
         draw_emojis: bool = False,
         wrap_style: WrapStyle = WrapStyle.Word,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        markup: Optional[Markup] = None,
+        max_lines: Optional[int] = None,
+        overflow: Overflow = Overflow.Clip,
+        ellipsis: str = "…",
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        background: Optional[Paint] = None,
+        padding: Union[float, tuple[float, float]] = 0.0,
+        corner_radius: float = 0.0,
+        background_mode: BackgroundMode = BackgroundMode.Line,
+        highlights: Optional[list[tuple[int, int, Paint]]] = None,
+        shaping: bool = False,
+        direction: Direction = Direction.Auto,
+        features: Optional[dict[str, int]] = None,
     ) -> None:
         """Draw text on the image.
 
@@ -274,10 +405,30 @@ class Writer:
             fill (Paint): The fill paint.
             line_spacing (float, optional): The line spacing. Defaults to 1.0.
             align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
-            stroke (float, optional): The stroke of the text. Defaults to None.
+            stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
             stroke_color (Paint, optional): The stroke color. Defaults to None.
             draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
             wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            markup (Markup, optional): Parse the text as markup with this parser. Defaults to None.
+            max_lines (int, optional): The most lines to draw. Defaults to None.
+            overflow (Overflow, optional): How the last line ends when there are more than max_lines. Defaults to Overflow.Clip.
+            ellipsis (str, optional): The text ending the last line with Overflow.Ellipsis. Defaults to "…".
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            background (Paint, optional): The paint of a box drawn behind the text. Defaults to None.
+            padding (float | tuple[float, float], optional): The padding of the background box, or its (horizontal, vertical) padding. Defaults to 0.0.
+            corner_radius (float, optional): The corner radius of the background box. Defaults to 0.0.
+            background_mode (BackgroundMode, optional): Whether each line gets its own background box or the whole text shares one. Defaults to BackgroundMode.Line.
+            highlights (list[tuple[int, int, Paint]], optional): Ranges of characters (start, end) of the text, without any markup tags, painted behind their glyphs. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
         """
         draw_text_wrapped(
             self._canvas,
@@ -296,4 +447,196 @@ class Writer:
             stroke_color,
             draw_emojis,
             wrap_style,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            markup,
+            max_lines,
+            overflow,
+            ellipsis,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            background,
+            padding,
+            corner_radius,
+            background_mode,
+            highlights,
+            shaping,
+            direction,
+            features,
+        )
+
+    def draw_text_fitted(
+        self,
+        text: str,
+        x: float,
+        y: float,
+        ax: float,
+        ay: float,
+        width: float,
+        height: float,
+        font: Font,
+        fill: Paint,
+        min_size: float = 1.0,
+        max_size: Optional[float] = None,
+        line_spacing: float = 1.0,
+        align: TextAlign = TextAlign.Left,
+        stroke: Optional[Union[float, Stroke]] = None,
+        stroke_color: Optional[Paint] = None,
+        draw_emojis: bool = False,
+        wrap_style: WrapStyle = WrapStyle.Word,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        shaping: bool = False,
+        direction: Direction = Direction.Auto,
+        features: Optional[dict[str, int]] = None,
+    ) -> float:
+        """Draw text wrapped at the largest size that fits a box.
+
+        Args:
+            text (str): The text to draw.
+            x (float): The x position of the text.
+            y (float): The y position of the text.
+            ax (float): The x anchor of the text.
+            ay (float): The y anchor of the text.
+            width (float): The width of the box.
+            height (float): The height of the box.
+            font (Font): The font to use.
+            fill (Paint): The fill paint.
+            min_size (float, optional): The smallest size to try, used when no size fits. Defaults to 1.0.
+            max_size (float, optional): The largest size to try. Defaults to the height of the box.
+            line_spacing (float, optional): The line spacing. Defaults to 1.0.
+            align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
+            stroke (float | Stroke, optional): The stroke width of the text, or its stroke. Defaults to None.
+            stroke_color (Paint, optional): The stroke color of the text. Defaults to None.
+            draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+            wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+
+        Returns:
+            float: The size the text was drawn at.
+        """
+        return draw_text_fitted(
+            self._canvas,
+            text,
+            x,
+            y,
+            ax,
+            ay,
+            width,
+            height,
+            font,
+            fill,
+            min_size,
+            max_size,
+            line_spacing,
+            align,
+            stroke,
+            stroke_color,
+            draw_emojis,
+            wrap_style,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            shaping,
+            direction,
+            features,
+        )
+
+    def draw_rich_text(
+        self,
+        spans: list[Span],
+        x: float,
+        y: float,
+        ax: float,
+        ay: float,
+        width: Optional[float] = None,
+        line_spacing: float = 1.0,
+        align: TextAlign = TextAlign.Left,
+        draw_emojis: bool = False,
+        wrap_style: WrapStyle = WrapStyle.Word,
+        rotation: Optional[float] = None,
+        transform: Optional[tuple[float, float, float, float, float, float]] = None,
+        shadow: Optional[Shadow] = None,
+        glow: Optional[Shadow] = None,
+        outlines: Optional[list[tuple[Union[float, Stroke], Paint]]] = None,
+        letter_spacing: float = 0.0,
+        word_spacing: float = 0.0,
+        decorations: Optional[list[Decoration]] = None,
+        shaping: bool = False,
+        direction: Direction = Direction.Auto,
+        features: Optional[dict[str, int]] = None,
+    ) -> None:
+        """Draw spans of rich text on the image as one paragraph.
+
+        Args:
+            spans (list[Span]): The spans to draw, in order.
+            x (float): The x position of the text.
+            y (float): The y position of the text.
+            ax (float): The x anchor of the text.
+            ay (float): The y anchor of the text.
+            width (float, optional): The width to wrap the text to. Defaults to None.
+            line_spacing (float, optional): The line spacing, relative to the largest span on each line. Defaults to 1.0.
+            align (TextAlign, optional): The text alignment. Defaults to TextAlign.Left.
+            draw_emojis (bool, optional): Whether to draw emojis. Defaults to False.
+            wrap_style (WrapStyle, optional): The wrap style. Defaults to WrapStyle.Word.
+            rotation (float, optional): The rotation of the text in degrees, around the anchor point. Defaults to None.
+            transform (tuple[float, float, float, float, float, float], optional): An affine matrix (a, b, c, d, e, f) applied around the anchor point after the rotation. Defaults to None.
+            shadow (Shadow, optional): A shadow drawn beneath the text. Defaults to None.
+            glow (Shadow, optional): A glow drawn around the text, its offset is ignored. Defaults to None.
+            outlines (list[tuple[float | Stroke, Paint]], optional): Outline layers drawn beneath the fill, the widest outermost. Defaults to None.
+            letter_spacing (float, optional): Extra pixels added between characters. Defaults to 0.0.
+            word_spacing (float, optional): Extra pixels added after each whitespace character. Defaults to 0.0.
+            decorations (list[Decoration], optional): Lines drawn under, through or over the text. Defaults to None.
+            shaping (bool, optional): Whether to shape the text with its OpenType tables, which complex scripts and ligatures need. Defaults to False.
+            direction (Direction, optional): The direction paragraphs run in, Direction.Auto takes it from their first strong character. Right to left and mixed text is reordered line by line, and TextAlign.Start and TextAlign.End follow it. Defaults to Direction.Auto.
+            features (dict[str, int], optional): OpenType features to turn on or off, like {"tnum": 1, "liga": 0, "ss01": 1}, overriding the font's own. Text with features is shaped. Defaults to None.
+        """
+        draw_rich_text(
+            self._canvas,
+            spans,
+            x,
+            y,
+            ax,
+            ay,
+            width,
+            line_spacing,
+            align,
+            draw_emojis,
+            wrap_style,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            letter_spacing,
+            word_spacing,
+            decorations,
+            shaping,
+            direction,
+            features,
         )
@@ -0,0 +1,118 @@
+use unicode_bidi::{BidiClass, BidiInfo, Level};
+
+use crate::objects::Direction;
+
+/// Whether `text` has to be reordered before it's drawn, because it has
+/// right to left characters or runs right to left.
+pub fn is_needed(text: &str, direction: Direction) -> bool {
+    direction == Direction::Rtl
+        || text.chars().any(|c| {
+            matches!(
+                unicode_bidi::bidi_class(c),
+                BidiClass::R
+                    | BidiClass::AL
+                    | BidiClass::AN
+                    | BidiClass::RLE
+                    | BidiClass::RLO
+                    | BidiClass::RLI
+            )
+        })
+}
+
+/// The embedding level of each character of a paragraph, and whether the
+/// paragraph runs right to left. Left to right paragraphs without right to
+/// left characters get no levels, all of them being 0.
+///
+/// Auto paragraphs take the direction of their first strong character.
+pub fn levels(text: &str, direction: Direction) -> (Vec<u8>, bool) {
+    if !is_needed(text, direction) {
+        return (Vec::new(), false);
+    }
+
+    let level = match direction {
+        Direction::Auto => None,
+        Direction::Ltr => Some(Level::ltr()),
+        Direction::Rtl => Some(Level::rtl()),
+    };
+    let info = BidiInfo::new(text, level);
+    let rtl = match info.paragraphs.first() {
+        Some(paragraph) => paragraph.level.is_rtl(),
+        None => direction == Direction::Rtl,
+    };
+
+    let levels = text
+        .char_indices()
+        .map(|(byte, _)| info.levels[byte].number())
+        .collect();
+    (levels, rtl)
+}
+
+/// The character drawn for `c` at a bidi `level`. Brackets and other paired
+/// characters at odd levels are swapped for their mirrored forms, so that
+/// they face the right way once reordered.
+pub fn mirror(c: char, level: u8) -> char {
+    match level % 2 {
+        1 => unicode_bidi_mirroring::get_mirrored(c).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// The visual order of items at bidi `levels`, lowest first, reversing every
+/// run at or above each odd level from the highest level down.
+pub fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let (lowest, highest) = match (levels.iter().min(), levels.iter().max()) {
+        (Some(&lowest), Some(&highest)) => (lowest | 1, highest),
+        _ => return order,
+    };
+
+    for level in (lowest..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn left_to_right_text_needs_no_levels() {
+        assert_eq!(levels("abc", Direction::Ltr), (Vec::new(), false));
+        assert_eq!(levels("abc", Direction::Auto), (Vec::new(), false));
+        assert_eq!(levels("ab", Direction::Rtl), (vec![2, 2], true));
+    }
+
+    #[test]
+    fn auto_paragraphs_follow_their_first_strong_character() {
+        assert_eq!(levels("אב c", Direction::Auto), (vec![1, 1, 1, 2], true));
+        assert_eq!(levels("a אב", Direction::Auto), (vec![0, 0, 1, 1], false));
+    }
+
+    #[test]
+    fn odd_levels_reverse() {
+        assert_eq!(visual_order(&[0, 0, 1, 1, 0]), [0, 1, 3, 2, 4]);
+        assert_eq!(visual_order(&[1, 1, 2, 2, 1]), [4, 2, 3, 1, 0]);
+        assert_eq!(visual_order(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn brackets_mirror_at_odd_levels() {
+        assert_eq!(mirror('(', 1), ')');
+        assert_eq!(mirror('[', 3), ']');
+        assert_eq!(mirror('(', 2), '(');
+        assert_eq!(mirror('a', 1), 'a');
+    }
+}
@@ -0,0 +1,233 @@
+use image::RgbaImage;
+
+use crate::objects::BlendMode;
+
+/// The overlapping region of `src` placed at (x, y) on `dst`,
+/// as (src_x, src_y, dst_x, dst_y, width, height).
+pub fn overlap(
+    dst: (u32, u32),
+    src: (u32, u32),
+    x: i64,
+    y: i64,
+) -> Option<(u32, u32, u32, u32, u32, u32)> {
+    let sx = (-x).max(0);
+    let sy = (-y).max(0);
+    let dx = x.max(0);
+    let dy = y.max(0);
+
+    let width = (src.0 as i64 - sx).min(dst.0 as i64 - dx);
+    let height = (src.1 as i64 - sy).min(dst.1 as i64 - dy);
+
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    Some((
+        sx as u32,
+        sy as u32,
+        dx as u32,
+        dy as u32,
+        width as u32,
+        height as u32,
+    ))
+}
+
+/// Composites `src` onto `dst` at (x, y), `src` must fit within `dst`.
+pub fn blend_image<C>(
+    dst: &mut image::ImageBuffer<image::Rgba<u8>, C>,
+    src: &RgbaImage,
+    x: u32,
+    y: u32,
+    opacity: f32,
+    mode: BlendMode,
+) where
+    C: std::ops::DerefMut<Target = [u8]>,
+{
+    for (sx, sy, src_px) in src.enumerate_pixels() {
+        let dst_px = dst.get_pixel_mut(x + sx, y + sy);
+        dst_px.0 = blend_pixel(dst_px.0, src_px.0, opacity, mode);
+    }
+}
+
+fn blend_pixel(dst: [u8; 4], src: [u8; 4], opacity: f32, mode: BlendMode) -> [u8; 4] {
+    if matches!(mode, BlendMode::Normal) && src[3] == 255 && opacity >= 1.0 {
+        return src;
+    }
+
+    let a_s = src[3] as f32 / 255.0 * opacity;
+    let a_b = dst[3] as f32 / 255.0;
+    let a_o = a_s + a_b * (1.0 - a_s);
+
+    if a_o <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+
+    let mut out = [0u8; 4];
+    for i in 0..3 {
+        let c_s = src[i] as f32 / 255.0;
+        let c_b = dst[i] as f32 / 255.0;
+        let mixed = mode.blend(c_b, c_s);
+
+        let c_o = (a_s * (1.0 - a_b) * c_s + a_s * a_b * mixed + (1.0 - a_s) * a_b * c_b) / a_o;
+        out[i] = (c_o * 255.0).round().clamp(0.0, 255.0) as u8;
+    }
+    out[3] = (a_o * 255.0).round().clamp(0.0, 255.0) as u8;
+
+    out
+}
+
+impl BlendMode {
+    /// The separable blend function B(backdrop, source) from the W3C compositing spec.
+    fn blend(&self, c_b: f32, c_s: f32) -> f32 {
+        fn multiply(c_b: f32, c_s: f32) -> f32 {
+            c_b * c_s
+        }
+
+        fn screen(c_b: f32, c_s: f32) -> f32 {
+            c_b + c_s - c_b * c_s
+        }
+
+        fn hard_light(c_b: f32, c_s: f32) -> f32 {
+            if c_s <= 0.5 {
+                multiply(c_b, 2.0 * c_s)
+            } else {
+                screen(c_b, 2.0 * c_s - 1.0)
+            }
+        }
+
+        match self {
+            BlendMode::Normal => c_s,
+            BlendMode::Multiply => multiply(c_b, c_s),
+            BlendMode::Screen => screen(c_b, c_s),
+            BlendMode::Overlay => hard_light(c_s, c_b),
+            BlendMode::Darken => c_b.min(c_s),
+            BlendMode::Lighten => c_b.max(c_s),
+            BlendMode::ColorDodge => {
+                if c_b == 0.0 {
+                    0.0
+                } else if c_s >= 1.0 {
+                    1.0
+                } else {
+                    (c_b / (1.0 - c_s)).min(1.0)
+                }
+            }
+            BlendMode::ColorBurn => {
+                if c_b >= 1.0 {
+                    1.0
+                } else if c_s == 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - c_b) / c_s).min(1.0)
+                }
+            }
+            BlendMode::HardLight => hard_light(c_b, c_s),
+            BlendMode::SoftLight => {
+                if c_s <= 0.5 {
+                    c_b - (1.0 - 2.0 * c_s) * c_b * (1.0 - c_b)
+                } else {
+                    let d = if c_b <= 0.25 {
+                        ((16.0 * c_b - 12.0) * c_b + 4.0) * c_b
+                    } else {
+                        c_b.sqrt()
+                    };
+                    c_b + (2.0 * c_s - 1.0) * (d - c_b)
+                }
+            }
+            BlendMode::Difference => (c_b - c_s).abs(),
+            BlendMode::Exclusion => c_b + c_s - 2.0 * c_b * c_s,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap_clips_to_both_images() {
+        assert_eq!(overlap((10, 10), (4, 4), 2, 3), Some((0, 0, 2, 3, 4, 4)));
+        assert_eq!(overlap((10, 10), (4, 4), -1, 8), Some((1, 0, 0, 8, 3, 2)));
+        assert_eq!(
+            overlap((10, 10), (20, 20), -5, -5),
+            Some((5, 5, 0, 0, 10, 10))
+        );
+        assert_eq!(overlap((10, 10), (4, 4), 10, 0), None);
+        assert_eq!(overlap((10, 10), (4, 4), -4, 0), None);
+    }
+
+    #[test]
+    fn opaque_normal_replaces() {
+        let src = [10, 20, 30, 255];
+        assert_eq!(
+            blend_pixel([200, 100, 50, 255], src, 1.0, BlendMode::Normal),
+            src
+        );
+        assert_eq!(
+            blend_pixel([0, 0, 0, 0], src, 1.0, BlendMode::Multiply),
+            src
+        );
+    }
+
+    #[test]
+    fn source_over_alpha() {
+        // half transparent white over opaque black
+        let out = blend_pixel([0, 0, 0, 255], [255, 255, 255, 128], 1.0, BlendMode::Normal);
+        assert_eq!(out, [128, 128, 128, 255]);
+
+        // opacity scales the source alpha
+        let out = blend_pixel([0, 0, 0, 0], [255, 0, 0, 255], 0.5, BlendMode::Normal);
+        assert_eq!(out, [255, 0, 0, 128]);
+
+        assert_eq!(
+            blend_pixel([0, 0, 0, 0], [255, 0, 0, 0], 1.0, BlendMode::Screen),
+            [0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn separable_modes() {
+        let dst = [200, 100, 0, 255];
+        let src = [100, 100, 255, 255];
+        let blend = |mode| blend_pixel(dst, src, 1.0, mode);
+
+        assert_eq!(blend(BlendMode::Multiply), [78, 39, 0, 255]);
+        assert_eq!(blend(BlendMode::Screen), [222, 161, 255, 255]);
+        assert_eq!(blend(BlendMode::Darken), [100, 100, 0, 255]);
+        assert_eq!(blend(BlendMode::Lighten), [200, 100, 255, 255]);
+        assert_eq!(blend(BlendMode::Difference), [100, 0, 255, 255]);
+        assert_eq!(blend(BlendMode::Exclusion), [143, 122, 255, 255]);
+        assert_eq!(blend(BlendMode::Overlay), [188, 78, 0, 255]);
+        assert_eq!(blend(BlendMode::HardLight), [157, 78, 255, 255]);
+    }
+
+    #[test]
+    fn dodge_and_burn_edges() {
+        assert_eq!(BlendMode::ColorDodge.blend(0.0, 1.0), 0.0);
+        assert_eq!(BlendMode::ColorDodge.blend(0.5, 1.0), 1.0);
+        assert_eq!(BlendMode::ColorDodge.blend(0.25, 0.5), 0.5);
+        assert_eq!(BlendMode::ColorBurn.blend(1.0, 0.0), 1.0);
+        assert_eq!(BlendMode::ColorBurn.blend(0.5, 0.0), 0.0);
+        assert_eq!(BlendMode::ColorBurn.blend(0.75, 0.5), 0.5);
+    }
+
+    #[test]
+    fn soft_light_is_identity_at_half() {
+        for c_b in [0.0, 0.1, 0.3, 0.5, 0.9, 1.0] {
+            assert!((BlendMode::SoftLight.blend(c_b, 0.5) - c_b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn blend_image_at_offset() {
+        let mut dst = RgbaImage::from_pixel(3, 3, image::Rgba([0, 0, 0, 255]));
+        let src = RgbaImage::from_pixel(2, 1, image::Rgba([255, 255, 255, 255]));
+        blend_image(&mut dst, &src, 1, 2, 1.0, BlendMode::Normal);
+
+        let white: Vec<_> = dst
+            .enumerate_pixels()
+            .filter(|(_, _, px)| px.0[0] == 255)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(white, [(1, 2), (2, 2)]);
+    }
+}
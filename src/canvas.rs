@@ -1,21 +1,147 @@
-use pyo3::{prelude::*, types::PyBytes};
+use image::ImageEncoder;
+use pyo3::{buffer::PyBuffer, exceptions::PyBufferError, ffi, prelude::*, types::PyBytes};
+use std::ops::{Deref, DerefMut};
+use std::os::raw::{c_char, c_int, c_void};
 use std::sync::{Arc, RwLock};
 
-use crate::objects::Color;
+use crate::blend;
+use crate::objects::{BlendMode, Color};
+use crate::paint::Paint;
+use crate::shapes::{self, Path};
+use crate::stroke::{self, Stroke, StrokeArg};
 
 #[derive(Clone)]
 #[pyclass]
-pub struct Canvas(pub Arc<RwLock<image::RgbaImage>>);
+pub struct Canvas(pub Arc<RwLock<Image>>);
+
+/// The RGBA pixels of a canvas, row by row.
+pub type Image = image::ImageBuffer<image::Rgba<u8>, Pixels>;
+
+/// Where the pixels of a canvas live: in memory of its own, or in the
+/// writable buffer of a Python object, like a numpy array, which is kept
+/// alive along with them.
+pub enum Pixels {
+    Owned(Vec<u8>),
+    Shared(PyBuffer<u8>),
+}
+
+impl Deref for Pixels {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Pixels::Owned(data) => data,
+            // SAFETY: the buffer was checked to be c-contiguous bytes, and
+            // stays valid until it's released on drop
+            Pixels::Shared(buffer) => unsafe {
+                std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.item_count())
+            },
+        }
+    }
+}
+
+impl DerefMut for Pixels {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Pixels::Owned(data) => data,
+            // SAFETY: as above, and the buffer was checked to be writable
+            Pixels::Shared(buffer) => unsafe {
+                std::slice::from_raw_parts_mut(buffer.buf_ptr() as *mut u8, buffer.item_count())
+            },
+        }
+    }
+}
+
+impl From<image::RgbaImage> for Canvas {
+    fn from(im: image::RgbaImage) -> Self {
+        let (width, height) = im.dimensions();
+        let pixels = Pixels::Owned(im.into_raw());
+        // the pixels came from an image of this size
+        Canvas(Arc::new(RwLock::new(
+            Image::from_raw(width, height, pixels).unwrap(),
+        )))
+    }
+}
+
+/// Runs `f` with the pixels as an [`image::RgbaImage`], which imagetext
+/// draws onto. Pixels of the canvas's own are moved in and back out, shared
+/// pixels are copied.
+pub fn with_rgba<R>(im: &mut Image, f: impl FnOnce(&mut image::RgbaImage) -> R) -> R {
+    let (width, height) = im.dimensions();
+    let empty = Image::from_raw(0, 0, Pixels::Owned(Vec::new())).unwrap();
+    let mut pixels = std::mem::replace(im, empty).into_raw();
+
+    // moving the vec keeps its allocation, which views of the canvas point to
+    let data = match &mut pixels {
+        Pixels::Owned(data) => std::mem::take(data),
+        Pixels::Shared(_) => pixels.to_vec(),
+    };
+    let mut rgba = image::RgbaImage::from_raw(width, height, data).unwrap();
+    let result = f(&mut rgba);
+
+    match &mut pixels {
+        Pixels::Owned(data) => *data = rgba.into_raw(),
+        Pixels::Shared(_) => pixels.copy_from_slice(&rgba),
+    }
+    *im = Image::from_raw(width, height, pixels).unwrap();
+    result
+}
+
+impl Canvas {
+    fn draw_path(
+        &self,
+        py: Python,
+        path: Option<tiny_skia::Path>,
+        paint: &Paint,
+        stroke: Option<Stroke>,
+    ) -> PyResult<()> {
+        // degenerate shapes (zero size, empty paths) have nothing to draw
+        let Some(path) = path else {
+            return Ok(());
+        };
+
+        // miters reach furthest from the path, placed strokes are drawn twice
+        // as wide, and antialiasing can touch one more pixel
+        let margin = stroke
+            .as_ref()
+            .map_or(0.0, |s| s.stroke.width * s.stroke.miter_limit.max(1.0))
+            + 1.0;
+        let Some(area) = path.bounds().outset(margin, margin) else {
+            return Ok(());
+        };
+
+        py.allow_threads(|| match self.0.write() {
+            Ok(mut im) => {
+                with_layer(&mut im, area, |pixmap, transform| match &stroke {
+                    Some(stroke) => {
+                        stroke::stroke_path(pixmap, &path, &paint.0, stroke, transform, None)
+                    }
+                    None => pixmap.fill_path(
+                        &path,
+                        &paint.0,
+                        tiny_skia::FillRule::Winding,
+                        transform,
+                        None,
+                    ),
+                });
+                Ok(())
+            }
+            Err(_) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Failed to lock image",
+            )),
+        })
+    }
+}
 
 #[pymethods]
 impl Canvas {
     #[new]
     fn new(width: u32, height: u32, color: Color) -> Self {
-        Canvas(Arc::new(RwLock::new(image::RgbaImage::from_pixel(
+        Canvas::from(image::RgbaImage::from_pixel(
             width,
             height,
             image::Rgba(color.0),
-        ))))
+        ))
     }
 
     fn save(&self, path: &str) -> PyResult<()> {
@@ -41,6 +167,154 @@ impl Canvas {
         }
     }
 
+    fn encode(
+        &self,
+        py: Python,
+        format: &str,
+        quality: Option<u8>,
+        compression: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let buffer = py.allow_threads(|| match self.0.read() {
+            Ok(im) => encode_image(&im, format, quality, compression),
+            Err(_) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Failed to lock image",
+            )),
+        })?;
+
+        Ok(PyBytes::new(py, &buffer).into())
+    }
+
+    fn paste(
+        &self,
+        py: Python,
+        other: Canvas,
+        x: i64,
+        y: i64,
+        opacity: Option<f32>,
+        blend_mode: Option<BlendMode>,
+    ) -> PyResult<()> {
+        let opacity = opacity.unwrap_or(1.0).clamp(0.0, 1.0);
+        let blend_mode = blend_mode.unwrap_or(BlendMode::Normal);
+
+        py.allow_threads(|| {
+            let dimensions = match self.0.read() {
+                Ok(im) => im.dimensions(),
+                Err(_) => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "Failed to lock image",
+                    ))
+                }
+            };
+
+            // copy the overlapping part of the source out first, so pasting a canvas
+            // onto itself (or two canvases onto each other) never holds both locks
+            let (src, dx, dy) = match other.0.read() {
+                Ok(src) => match blend::overlap(dimensions, src.dimensions(), x, y) {
+                    Some((sx, sy, dx, dy, width, height)) => (
+                        image::imageops::crop_imm(&*src, sx, sy, width, height).to_image(),
+                        dx,
+                        dy,
+                    ),
+                    None => return Ok(()),
+                },
+                Err(_) => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "Failed to lock image",
+                    ))
+                }
+            };
+
+            match self.0.write() {
+                Ok(mut im) => {
+                    blend::blend_image(&mut im, &src, dx, dy, opacity, blend_mode);
+                    Ok(())
+                }
+                Err(_) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Failed to lock image",
+                )),
+            }
+        })
+    }
+
+    fn fill_rect(
+        &self,
+        py: Python,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        paint: &Paint,
+    ) -> PyResult<()> {
+        self.draw_path(py, shapes::rect(x, y, width, height), paint, None)
+    }
+
+    fn fill_rounded_rect(
+        &self,
+        py: Python,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        radius: f32,
+        paint: &Paint,
+    ) -> PyResult<()> {
+        self.draw_path(
+            py,
+            shapes::rounded_rect(x, y, width, height, radius),
+            paint,
+            None,
+        )
+    }
+
+    fn fill_ellipse(
+        &self,
+        py: Python,
+        cx: f32,
+        cy: f32,
+        rx: f32,
+        ry: f32,
+        paint: &Paint,
+    ) -> PyResult<()> {
+        self.draw_path(py, shapes::ellipse(cx, cy, rx, ry), paint, None)
+    }
+
+    fn stroke_line(
+        &self,
+        py: Python,
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        width: StrokeArg,
+        paint: &Paint,
+    ) -> PyResult<()> {
+        self.draw_path(
+            py,
+            shapes::line(x0, y0, x1, y1),
+            paint,
+            Some(width.into_stroke()),
+        )
+    }
+
+    fn fill_path(&self, py: Python, path: &Path, paint: &Paint) -> PyResult<()> {
+        self.draw_path(py, path.0.clone().finish(), paint, None)
+    }
+
+    fn stroke_path(
+        &self,
+        py: Python,
+        path: &Path,
+        width: StrokeArg,
+        paint: &Paint,
+    ) -> PyResult<()> {
+        self.draw_path(
+            py,
+            path.0.clone().finish(),
+            paint,
+            Some(width.into_stroke()),
+        )
+    }
+
     fn as_list(&self) -> PyResult<Vec<u8>> {
         match self.0.read() {
             Ok(im) => Ok(im.to_vec()),
@@ -50,6 +324,132 @@ impl Canvas {
         }
     }
 
+    /// Exports the pixels as a writable HxWx4 buffer of bytes. The view
+    /// holds a reference to the canvas, whose pixels never move, so they
+    /// outlive it.
+    unsafe fn __getbuffer__(
+        slf: PyRef<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if flags & ffi::PyBUF_F_CONTIGUOUS == ffi::PyBUF_F_CONTIGUOUS {
+            return Err(PyBufferError::new_err(
+                "The canvas is not Fortran contiguous",
+            ));
+        }
+
+        let (buf, width, height) = match slf.0.write() {
+            Ok(mut im) => {
+                let (width, height) = im.dimensions();
+                (
+                    im.as_mut_ptr(),
+                    width as ffi::Py_ssize_t,
+                    height as ffi::Py_ssize_t,
+                )
+            }
+            Err(_) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Failed to lock image",
+                ))
+            }
+        };
+
+        // the shape and strides, freed when the view is released
+        let layout = Box::into_raw(Box::new([height, width, 4, width * 4, 4, 1]));
+        let layout = layout as *mut ffi::Py_ssize_t;
+
+        let view = &mut *view;
+        view.obj = slf.into_ptr();
+        view.buf = buf as *mut c_void;
+        view.len = height * width * 4;
+        view.readonly = 0;
+        view.itemsize = 1;
+        view.format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+            c"B".as_ptr() as *mut c_char
+        } else {
+            std::ptr::null_mut()
+        };
+        (view.ndim, view.shape) = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+            (3, layout)
+        } else {
+            (1, std::ptr::null_mut())
+        };
+        view.strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+            layout.add(3)
+        } else {
+            std::ptr::null_mut()
+        };
+        view.suboffsets = std::ptr::null_mut();
+        view.internal = layout as *mut c_void;
+
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, view: *mut ffi::Py_buffer) {
+        drop(Box::from_raw((*view).internal as *mut [ffi::Py_ssize_t; 6]));
+    }
+
+    /// A canvas drawing onto an HxWx4 array of bytes, sharing its memory and
+    /// keeping it alive. Read-only arrays are copied.
+    #[staticmethod]
+    fn from_array(py: Python, array: &PyAny) -> PyResult<Self> {
+        let shape_error =
+            || PyErr::new::<pyo3::exceptions::PyValueError, _>("Expected a HxWx4 uint8 array");
+        let buffer = PyBuffer::<u8>::get(array).map_err(|_| shape_error())?;
+        let shape = buffer.shape();
+        if buffer.dimensions() != 3 || shape[2] != 4 {
+            return Err(shape_error());
+        }
+        // explicit strides are fine as long as they lay out the rows in order
+        if !buffer.is_c_contiguous() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Expected a C-contiguous array",
+            ));
+        }
+
+        let (width, height) = match (u32::try_from(shape[1]), u32::try_from(shape[0])) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(shape_error()),
+        };
+        let pixels = if buffer.readonly() {
+            Pixels::Owned(buffer.to_vec(py)?)
+        } else {
+            Pixels::Shared(buffer)
+        };
+
+        Ok(Canvas(Arc::new(RwLock::new(
+            Image::from_raw(width, height, pixels).ok_or_else(shape_error)?,
+        ))))
+    }
+
+    #[staticmethod]
+    fn open(py: Python, path: &str) -> PyResult<Self> {
+        let im = py
+            .allow_threads(|| image::open(path).map(|im| im.into_rgba8()))
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to open image: {}", e))
+            })?;
+
+        Ok(Canvas::from(im))
+    }
+
+    #[staticmethod]
+    fn from_encoded(py: Python, data: &[u8]) -> PyResult<Self> {
+        let im = py
+            .allow_threads(|| image::load_from_memory(data).map(|im| im.into_rgba8()))
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to decode image: {}",
+                    e
+                ))
+            })?;
+
+        Ok(Canvas::from(im))
+    }
+
     #[staticmethod]
     fn from_image(mut image: &PyAny) -> PyResult<Self> {
         let mode: &str = image.getattr("mode")?.extract()?;
@@ -60,14 +460,14 @@ impl Canvas {
         }
         let buffer: Vec<u8> = image.call_method0("tobytes")?.extract()?;
 
-        Ok(Canvas(Arc::new(RwLock::new(
+        Ok(Canvas::from(
             image::RgbaImage::from_raw(width, height, buffer).ok_or(PyErr::new::<
                 pyo3::exceptions::PyValueError,
                 _,
             >(
                 "Failed to convert image"
             ))?,
-        ))))
+        ))
     }
 
     fn to_image(&self) -> PyResult<PyObject> {
@@ -83,3 +483,370 @@ impl Canvas {
         })
     }
 }
+
+/// Runs `f` with a tiny-skia pixmap covering `area` of the image, then draws
+/// what it painted over the image.
+///
+/// tiny-skia works with premultiplied colors, so `f` draws onto a transparent
+/// layer instead of the image pixels, and is given the transform mapping
+/// image space onto the layer. Pixels the layer leaves transparent are not
+/// touched. Returns `None` when `area` is outside of the image.
+pub fn with_layer<C, R>(
+    im: &mut image::ImageBuffer<image::Rgba<u8>, C>,
+    area: tiny_skia::Rect,
+    f: impl FnOnce(&mut tiny_skia::PixmapMut, tiny_skia::Transform) -> R,
+) -> Option<R>
+where
+    C: DerefMut<Target = [u8]>,
+{
+    let area = area.round_out()?.intersect(&tiny_skia::IntRect::from_xywh(
+        0,
+        0,
+        im.width(),
+        im.height(),
+    )?)?;
+    let mut layer = tiny_skia::Pixmap::new(area.width(), area.height())?;
+
+    let to_layer = tiny_skia::Transform::from_translate(-area.x() as f32, -area.y() as f32);
+    let result = f(&mut layer.as_mut(), to_layer);
+    composite(im, &layer, area.x() as u32, area.y() as u32);
+
+    Some(result)
+}
+
+/// Draws a premultiplied layer over the image with its top left corner at
+/// (x, y), the layer must fit within the image.
+fn composite<C>(
+    im: &mut image::ImageBuffer<image::Rgba<u8>, C>,
+    layer: &tiny_skia::Pixmap,
+    x: u32,
+    y: u32,
+) where
+    C: DerefMut<Target = [u8]>,
+{
+    for (i, src) in layer.pixels().iter().enumerate() {
+        let src_a = src.alpha();
+        if src_a == 0 {
+            continue;
+        }
+
+        let i = i as u32;
+        let dst = im.get_pixel_mut(x + i % layer.width(), y + i / layer.width());
+        let src = [src.red(), src.green(), src.blue()];
+        if src_a == 255 {
+            dst.0 = [src[0], src[1], src[2], 255];
+            continue;
+        }
+
+        // source over, with the source already multiplied by its alpha
+        let src_a = src_a as f32 / 255.0;
+        let dst_a = dst.0[3] as f32 / 255.0 * (1.0 - src_a);
+        let out_a = src_a + dst_a;
+        for (c, s) in dst.0[..3].iter_mut().zip(src) {
+            *c = ((s as f32 + *c as f32 * dst_a) / out_a).round().min(255.0) as u8;
+        }
+        dst.0[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+fn encode_image<C>(
+    im: &image::ImageBuffer<image::Rgba<u8>, C>,
+    format: &str,
+    quality: Option<u8>,
+    compression: Option<&str>,
+) -> PyResult<Vec<u8>>
+where
+    C: Deref<Target = [u8]>,
+{
+    let (width, height) = im.dimensions();
+    let mut buffer = std::io::Cursor::new(Vec::new());
+
+    let result = match format.to_ascii_lowercase().as_str() {
+        "png" => {
+            let compression = match compression.unwrap_or("default") {
+                "default" => image::codecs::png::CompressionType::Default,
+                "fast" => image::codecs::png::CompressionType::Fast,
+                "best" => image::codecs::png::CompressionType::Best,
+                other => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Unknown png compression: {}",
+                        other
+                    )))
+                }
+            };
+
+            image::codecs::png::PngEncoder::new_with_quality(
+                &mut buffer,
+                compression,
+                image::codecs::png::FilterType::Adaptive,
+            )
+            .write_image(im, width, height, image::ColorType::Rgba8)
+        }
+        "jpeg" | "jpg" => {
+            // jpeg has no alpha channel, the encoder drops it
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality.unwrap_or(75))
+                .write_image(im, width, height, image::ColorType::Rgba8)
+        }
+        "webp" => {
+            let quality = match quality {
+                Some(quality) => image::codecs::webp::WebPQuality::lossy(quality),
+                None => image::codecs::webp::WebPQuality::lossless(),
+            };
+
+            image::codecs::webp::WebPEncoder::new_with_quality(&mut buffer, quality).write_image(
+                im,
+                width,
+                height,
+                image::ColorType::Rgba8,
+            )
+        }
+        "bmp" => image::codecs::bmp::BmpEncoder::new(&mut buffer).write_image(
+            im,
+            width,
+            height,
+            image::ColorType::Rgba8,
+        ),
+        "tiff" | "tif" => image::codecs::tiff::TiffEncoder::new(&mut buffer).write_image(
+            im,
+            width,
+            height,
+            image::ColorType::Rgba8,
+        ),
+        other => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unsupported image format: {}",
+                other
+            )))
+        }
+    };
+
+    result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to encode image: {}", e))
+    })?;
+
+    Ok(buffer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> image::RgbaImage {
+        image::RgbaImage::from_fn(7, 5, |x, y| {
+            image::Rgba([
+                (x * 30) as u8,
+                (y * 50) as u8,
+                200,
+                (x * 20 + y * 10 + 5) as u8,
+            ])
+        })
+    }
+
+    fn decode(data: &[u8]) -> image::RgbaImage {
+        image::load_from_memory(data).unwrap().into_rgba8()
+    }
+
+    #[test]
+    fn lossless_formats_round_trip() {
+        let im = image();
+        for format in ["png", "PNG", "webp", "bmp", "tiff", "tif"] {
+            let data = encode_image(&im, format, None, None).unwrap();
+            assert_eq!(decode(&data), im, "{}", format);
+        }
+        for compression in ["fast", "best"] {
+            let data = encode_image(&im, "png", None, Some(compression)).unwrap();
+            assert_eq!(decode(&data), im, "{}", compression);
+        }
+    }
+
+    #[test]
+    fn lossy_formats_keep_the_size() {
+        let im = image();
+        for (format, quality) in [("jpeg", None), ("jpg", Some(90)), ("webp", Some(50))] {
+            let data = encode_image(&im, format, quality, None).unwrap();
+            assert_eq!(decode(&data).dimensions(), im.dimensions(), "{}", format);
+        }
+    }
+
+    fn bytes<'py>(py: Python<'py>, im: image::RgbaImage, readonly: bool) -> &'py PyAny {
+        let (width, height) = im.dimensions();
+        let data: PyObject = match readonly {
+            true => PyBytes::new(py, &im).into(),
+            false => pyo3::types::PyByteArray::new(py, &im).into(),
+        };
+        let view = py
+            .eval("memoryview", None, None)
+            .unwrap()
+            .call1((data,))
+            .unwrap();
+        view.call_method1("cast", ("B", (height, width, 4)))
+            .unwrap()
+    }
+
+    /// The pixel at row `y` and column `x` of an HxWx4 buffer.
+    fn pixel(view: &PyAny, y: usize, x: usize) -> [u8; 4] {
+        [0, 1, 2, 3].map(|c| view.get_item((y, x, c)).unwrap().extract().unwrap())
+    }
+
+    #[test]
+    fn buffers_view_the_canvas() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let canvas = Py::new(py, Canvas::from(image())).unwrap();
+            let view = py
+                .eval("memoryview", None, None)
+                .unwrap()
+                .call1((&canvas,))
+                .unwrap();
+            let shape: (usize, usize, usize) = view.getattr("shape").unwrap().extract().unwrap();
+            assert_eq!(shape, (5, 7, 4));
+            let data: Vec<u8> = view.call_method0("tobytes").unwrap().extract().unwrap();
+            assert_eq!(data, image().into_raw());
+
+            // drawing shows through the view, and writes through it reach the canvas
+            canvas.borrow(py).0.write().unwrap().get_pixel_mut(0, 0).0 = [1, 2, 3, 4];
+            assert_eq!(pixel(view, 0, 0), [1, 2, 3, 4]);
+            view.set_item((4, 6, 3), 9).unwrap();
+            assert_eq!(canvas.borrow(py).0.read().unwrap().get_pixel(6, 4).0[3], 9);
+
+            // the view keeps the canvas alive
+            drop(canvas);
+            view.set_item((0, 0, 0), 7).unwrap();
+            view.call_method0("release").unwrap();
+        });
+    }
+
+    #[test]
+    fn arrays_are_shared_with_the_canvas() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let array = bytes(py, image(), false);
+            let canvas = Canvas::from_array(py, array).unwrap();
+            assert_eq!(
+                canvas.0.read().unwrap().as_raw().to_vec(),
+                image().into_raw()
+            );
+
+            canvas.0.write().unwrap().get_pixel_mut(1, 0).0 = [1, 2, 3, 4];
+            assert_eq!(pixel(array, 0, 1), [1, 2, 3, 4]);
+
+            // imagetext draws onto a copy of shared pixels that's copied back
+            with_rgba(&mut canvas.0.write().unwrap(), |im| {
+                im.get_pixel_mut(2, 0).0 = [5; 4]
+            });
+            assert_eq!(pixel(array, 0, 2), [5; 4]);
+        });
+    }
+
+    #[test]
+    fn read_only_arrays_are_copied() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let canvas = Canvas::from_array(py, bytes(py, image(), true)).unwrap();
+            assert!(matches!(
+                canvas.0.read().unwrap().as_raw(),
+                Pixels::Owned(_)
+            ));
+            assert_eq!(
+                canvas.0.read().unwrap().as_raw().to_vec(),
+                image().into_raw()
+            );
+        });
+    }
+
+    #[test]
+    fn arrays_must_be_contiguous_pixels() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let error = |array: &PyAny| {
+                Canvas::from_array(py, array)
+                    .err()
+                    .unwrap()
+                    .value(py)
+                    .to_string()
+            };
+            let array = bytes(py, image(), false);
+            let every_other_row = array
+                .get_item(pyo3::types::PySlice::new(py, 0, 5, 2))
+                .unwrap();
+            assert_eq!(error(every_other_row), "Expected a C-contiguous array");
+
+            let flat = pyo3::types::PyByteArray::new(py, &[0; 16]);
+            assert_eq!(error(flat), "Expected a HxWx4 uint8 array");
+            let rgb = py
+                .eval("memoryview(bytearray(48)).cast('B', (4, 4, 3))", None, None)
+                .unwrap();
+            assert_eq!(error(rgb), "Expected a HxWx4 uint8 array");
+        });
+    }
+
+    #[test]
+    fn from_encoded_decodes_to_rgba() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let data = encode_image(&image(), "png", None, None).unwrap();
+            let canvas = Canvas::from_encoded(py, &data).unwrap();
+            assert_eq!(
+                canvas.0.read().unwrap().as_raw().to_vec(),
+                image().into_raw()
+            );
+
+            let gray = image::GrayImage::from_pixel(2, 3, image::Luma([90]));
+            let mut data = std::io::Cursor::new(Vec::new());
+            gray.write_to(&mut data, image::ImageOutputFormat::Bmp)
+                .unwrap();
+            let canvas = Canvas::from_encoded(py, data.get_ref()).unwrap();
+            let im = canvas.0.read().unwrap();
+            assert_eq!(im.dimensions(), (2, 3));
+            assert!(im.pixels().all(|px| px.0 == [90, 90, 90, 255]));
+
+            assert!(Canvas::from_encoded(py, b"not an image").is_err());
+        });
+    }
+
+    #[test]
+    fn layers_leave_untouched_pixels_alone() {
+        let mut im = image();
+        let rect = tiny_skia::Rect::from_xywh(2.0, 1.0, 2.0, 2.0).unwrap();
+        let path = tiny_skia::PathBuilder::from_rect(rect);
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(255, 0, 0, 255);
+
+        let area = path.bounds().outset(1.0, 1.0).unwrap();
+        with_layer(&mut im, area, |pixmap, transform| {
+            pixmap.fill_path(&path, &paint, tiny_skia::FillRule::Winding, transform, None);
+        })
+        .unwrap();
+
+        for (x, y, px) in im.enumerate_pixels() {
+            if (2..4).contains(&x) && (1..3).contains(&y) {
+                assert_eq!(px.0, [255, 0, 0, 255]);
+            } else {
+                assert_eq!(*px, *image().get_pixel(x, y), "({}, {})", x, y);
+            }
+        }
+
+        let outside = tiny_skia::Rect::from_xywh(20.0, 20.0, 5.0, 5.0).unwrap();
+        assert!(with_layer(&mut im, outside, |_, _| ()).is_none());
+    }
+
+    #[test]
+    fn layers_composite_source_over() {
+        let mut im = image::RgbaImage::from_pixel(2, 1, image::Rgba([0, 0, 255, 128]));
+        // half transparent white, premultiplied
+        let mut layer = tiny_skia::Pixmap::new(1, 1).unwrap();
+        layer.fill(tiny_skia::Color::from_rgba8(255, 255, 255, 128));
+        composite(&mut im, &layer, 1, 0);
+
+        assert_eq!(im.get_pixel(0, 0).0, [0, 0, 255, 128]);
+        // alpha 0.5 + 0.5 * 0.5, blue stays at full and white is two thirds
+        assert_eq!(im.get_pixel(1, 0).0, [170, 170, 255, 192]);
+    }
+
+    #[test]
+    fn unknown_options_are_errors() {
+        let im = image();
+        assert!(encode_image(&im, "gif", None, None).is_err());
+        assert!(encode_image(&im, "png", None, Some("smallest")).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use imagetext::prelude::*;
+use pyo3::prelude::*;
+use tiny_skia::{PathBuilder, Rect};
+
+use crate::font::face;
+use crate::layout::Block;
+use crate::objects::DecorationLine;
+use crate::paint;
+
+/// A line drawn under, through or over the text.
+#[pyclass]
+#[derive(Clone)]
+pub struct Decoration {
+    pub line: DecorationLine,
+    pub paint: Option<Paint<'static>>,
+    pub thickness: Option<f32>,
+    pub skip_ink: bool,
+}
+
+#[pymethods]
+impl Decoration {
+    #[new]
+    fn new(
+        line: &DecorationLine,
+        color: Option<&paint::Paint>,
+        thickness: Option<f32>,
+        skip_ink: Option<bool>,
+    ) -> Self {
+        Decoration {
+            line: *line,
+            paint: color.map(|c| c.0.clone()),
+            thickness: thickness.map(|t| t.max(0.0)),
+            skip_ink: skip_ink.unwrap_or(false),
+        }
+    }
+
+    #[getter]
+    fn line(&self) -> DecorationLine {
+        self.line
+    }
+
+    #[getter]
+    fn thickness(&self) -> Option<f32> {
+        self.thickness
+    }
+
+    #[getter]
+    fn skip_ink(&self) -> bool {
+        self.skip_ink
+    }
+}
+
+impl Decoration {
+    /// The top of the line below the baseline and its thickness, in pixels.
+    ///
+    /// Underlines come from the font's post table and strikethroughs from its
+    /// OS/2 table, fonts without them get lines placed like most fonts do.
+    /// Overlines are as thick as underlines and hang from the ascender.
+    pub fn metrics(&self, font: &SuperFont<'static>, scale: Scale) -> (f32, f32) {
+        let face = face(&font.main);
+        let units = font.main.scale_for_pixel_height(scale.y);
+        let em = face.units_per_em() as f32;
+
+        let underline = face
+            .underline_metrics()
+            .filter(|m| m.thickness > 0)
+            .map_or((-0.1 * em, em / 14.0), |m| {
+                (m.position as f32, m.thickness as f32)
+            });
+        let (position, thickness) = match self.line {
+            DecorationLine::Underline => underline,
+            DecorationLine::Strikethrough => face
+                .strikeout_metrics()
+                .filter(|m| m.thickness > 0)
+                .map_or((0.3 * em, underline.1), |m| {
+                    (m.position as f32, m.thickness as f32)
+                }),
+            DecorationLine::Overline => (face.ascender() as f32, underline.1),
+        };
+
+        // font units grow upwards, positions are of the top of the line
+        (
+            -position * units,
+            self.thickness.unwrap_or(thickness * units),
+        )
+    }
+
+    /// The decoration of each run on some lines of a block, in block space,
+    /// and the thickest line of them.
+    ///
+    /// Each line is decorated from its first glyph to its last one that
+    /// isn't whitespace, split where runs change.
+    pub fn paths(
+        &self,
+        block: &Block,
+        fonts: &[&SuperFont<'static>],
+        lines: std::ops::Range<usize>,
+    ) -> (Vec<Option<tiny_skia::Path>>, f32) {
+        let mut builders = vec![PathBuilder::new(); fonts.len()];
+        let mut thickest: f32 = 0.0;
+
+        for (line, x, y) in &block.lines[lines] {
+            let end = match line.glyphs.iter().rposition(|g| !g.is_blank()) {
+                Some(end) => end + 1,
+                None => continue,
+            };
+
+            for segment in line.glyphs[..end].chunk_by(|a, b| a.run == b.run) {
+                let (first, last) = (&segment[0], &segment[segment.len() - 1]);
+                let (top, thickness) = self.metrics(fonts[first.run], first.scale);
+                thickest = thickest.max(thickness);
+
+                if let Some(rect) = Rect::from_xywh(
+                    x + first.x,
+                    y + line.ascent + top,
+                    last.x + last.advance - first.x,
+                    thickness,
+                ) {
+                    builders[first.run].push_rect(rect);
+                }
+            }
+        }
+
+        (
+            builders.into_iter().map(PathBuilder::finish).collect(),
+            thickest,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{layout_line, Spacing};
+    use crate::objects::{Direction, TextAlign};
+    use crate::testing::superfont;
+
+    fn decoration(line: DecorationLine, thickness: Option<f32>) -> Decoration {
+        Decoration {
+            line,
+            paint: None,
+            thickness,
+            skip_ink: false,
+        }
+    }
+
+    #[test]
+    fn lines_are_placed_from_the_font() {
+        let font = superfont();
+        let size = scale(50.0);
+        let (under, thickness) = decoration(DecorationLine::Underline, None).metrics(&font, size);
+        let (through, _) = decoration(DecorationLine::Strikethrough, None).metrics(&font, size);
+        let (over, over_thickness) =
+            decoration(DecorationLine::Overline, None).metrics(&font, size);
+
+        // below the baseline, through the middle of the text, at the ascender
+        assert!(under > 0.0 && through < 0.0 && over < through);
+        assert!((over + font.main.v_metrics(size).ascent).abs() < 1.0);
+        assert!(thickness > 0.0 && thickness == over_thickness);
+
+        let (_, custom) = decoration(DecorationLine::Underline, Some(7.0)).metrics(&font, size);
+        assert_eq!(custom, 7.0);
+    }
+
+    #[test]
+    fn lines_end_at_the_last_ink() {
+        let font = superfont();
+        let line = layout_line(
+            "ab  ",
+            &font,
+            scale(20.0),
+            false,
+            Spacing::default(),
+            None,
+            Direction::Ltr,
+        );
+        let last = &line.glyphs[1];
+        let end = last.x + last.advance;
+        let block = Block::new(vec![line], 1.0, &TextAlign::Left, None);
+
+        let (paths, thickest) =
+            decoration(DecorationLine::Underline, Some(3.0)).paths(&block, &[&font], 0..1);
+        let bounds = paths[0].as_ref().unwrap().bounds();
+        assert_eq!((bounds.left(), bounds.right()), (0.0, end));
+        assert_eq!((bounds.height(), thickest), (3.0, 3.0));
+
+        let blank = Block::new(Vec::new(), 1.0, &TextAlign::Left, None);
+        let (paths, _) = decoration(DecorationLine::Underline, None).paths(&blank, &[&font], 0..0);
+        assert!(paths[0].is_none());
+    }
+}
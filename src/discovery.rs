@@ -0,0 +1,584 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use imagetext::prelude::*;
+use unicode_script::{Script, UnicodeScript};
+
+use crate::font::{face, font_from_vec};
+use crate::layout::EMOJI_PLACEHOLDER;
+
+/// Fonts loaded into the FontDB, indexed by the characters they have glyphs
+/// for, so fonts missing glyphs can borrow them at layout time. imagetext
+/// doesn't list the fonts it loads, so the same files are indexed here as
+/// they're loaded.
+static DISCOVERY: Mutex<Discovery> = Mutex::new(Discovery::new());
+
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+struct Discovery {
+    enabled: bool,
+    /// Font files that were loaded but aren't indexed yet, with the FontDB
+    /// name they were loaded with.
+    pending: Vec<(Option<String>, PathBuf)>,
+    /// Pending font files being indexed, without the lock held.
+    indexing: Vec<(Option<String>, PathBuf)>,
+    faces: Vec<Face>,
+    /// The face picked for a character in a style, or None without one.
+    cache: BTreeMap<(char, Style), Option<usize>>,
+}
+
+/// A face of a font file, summarized by its coverage and style. Its font is
+/// only loaded once it's picked for a character.
+struct Face {
+    name: Option<String>,
+    path: Option<PathBuf>,
+    index: u32,
+    font: Option<rusttype::Font<'static>>,
+    /// Sorted, inclusive ranges of the codepoints the face has glyphs for.
+    ranges: Vec<(u32, u32)>,
+    style: Style,
+    /// How many characters of each script the face has glyphs for.
+    scripts: Vec<(Script, usize)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Style {
+    italic: bool,
+    weight: u16,
+}
+
+impl Style {
+    fn distance(self, other: Style) -> (bool, u16) {
+        (
+            self.italic != other.italic,
+            self.weight.abs_diff(other.weight),
+        )
+    }
+
+    fn of(face: &owned_ttf_parser::Face) -> Self {
+        Style {
+            italic: face.is_italic(),
+            weight: face.weight().to_number(),
+        }
+    }
+}
+
+impl Discovery {
+    const fn new() -> Self {
+        Discovery {
+            enabled: false,
+            pending: Vec::new(),
+            indexing: Vec::new(),
+            faces: Vec::new(),
+            cache: BTreeMap::new(),
+        }
+    }
+
+    /// The faces to fall back on for `missing` characters, closest in style
+    /// first, each of them drawing some of the characters.
+    fn fallbacks(&mut self, missing: &[char], style: Style) -> Vec<usize> {
+        let mut picked = Vec::new();
+        for &c in missing {
+            let i = match self.cache.get(&(c, style)) {
+                Some(&i) => i,
+                None => {
+                    let i = self.pick(c, style);
+                    self.cache.insert((c, style), i);
+                    i
+                }
+            };
+            if let Some(i) = i.filter(|i| !picked.contains(i)) {
+                picked.push(i);
+            }
+        }
+
+        // the first font of the chain with a glyph draws it, so the faces
+        // closest in style go first and faces left with nothing to draw are
+        // dropped
+        picked.sort_by_key(|&i| self.faces[i].style.distance(style));
+        let mut covered = BTreeSet::new();
+        picked.retain(|&i| {
+            let face = &self.faces[i];
+            let added = missing
+                .iter()
+                .filter(|&&c| face.covers(c) && covered.insert(c))
+                .count();
+            added > 0
+        });
+        picked
+    }
+
+    /// The face with a glyph for `c` covering the most of its script, and of
+    /// those that cover most of it, the one closest to `style`.
+    fn pick(&self, c: char, style: Style) -> Option<usize> {
+        let script = c.script();
+        let candidates: Vec<(usize, usize)> = self
+            .faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| face.covers(c))
+            .map(|(i, face)| (i, face.script_coverage(script)))
+            .collect();
+        let best = candidates.iter().map(|&(_, count)| count).max()?;
+
+        candidates
+            .into_iter()
+            .min_by_key(|&(i, count)| {
+                let face = &self.faces[i];
+                (count * 2 < best, face.style.distance(style), Reverse(count))
+            })
+            .map(|(i, _)| i)
+    }
+}
+
+impl Face {
+    fn new(
+        name: Option<String>,
+        path: Option<PathBuf>,
+        index: u32,
+        face: &owned_ttf_parser::Face,
+        font: Option<rusttype::Font<'static>>,
+    ) -> Self {
+        let ranges = coverage(face);
+        Face {
+            name,
+            path,
+            index,
+            font,
+            scripts: script_counts(&ranges),
+            ranges,
+            style: Style::of(face),
+        }
+    }
+
+    fn covers(&self, c: char) -> bool {
+        let c = c as u32;
+        self.ranges
+            .binary_search_by(|&(start, end)| {
+                if end < c {
+                    Ordering::Less
+                } else if start > c {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    fn script_coverage(&self, script: Script) -> usize {
+        self.scripts
+            .iter()
+            .find(|(s, _)| *s == script)
+            .map_or(0, |&(_, count)| count)
+    }
+}
+
+/// How many of the codepoints in `ranges` each script has.
+fn script_counts(ranges: &[(u32, u32)]) -> Vec<(Script, usize)> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    let chars = ranges
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .filter_map(char::from_u32);
+    for c in chars {
+        let script = c.script();
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    counts
+}
+
+/// The faces of a font file, or none when it can't be read. Files loaded
+/// under a name only have their first face in the FontDB, so it's the only
+/// one indexed.
+fn index_file(name: Option<String>, path: &Path) -> Vec<Face> {
+    let Ok(data) = std::fs::read(path) else {
+        return Vec::new();
+    };
+    let count = match name {
+        Some(_) => 1,
+        None => owned_ttf_parser::fonts_in_collection(&data).unwrap_or(1),
+    };
+    (0..count)
+        .filter_map(|index| {
+            let face = owned_ttf_parser::Face::from_slice(&data, index).ok()?;
+            Some(Face::new(
+                name.clone(),
+                Some(path.to_path_buf()),
+                index,
+                &face,
+                None,
+            ))
+        })
+        .collect()
+}
+
+/// Indexes the pending font files when finding fallbacks is turned on. Files
+/// are read and scanned without the lock held, so fonts can be looked up
+/// meanwhile.
+fn index_pending() {
+    let pending = {
+        let mut discovery = lock();
+        if !discovery.enabled {
+            return;
+        }
+        let pending = std::mem::take(&mut discovery.pending);
+        discovery.indexing.extend(pending.iter().cloned());
+        pending
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    let indexed: Vec<_> = pending
+        .into_iter()
+        .map(|(name, path)| {
+            let faces = index_file(name.clone(), &path);
+            ((name, path), faces)
+        })
+        .collect();
+
+    let mut discovery = lock();
+    for (file, faces) in indexed {
+        // files removed from the FontDB while they were indexed are dropped
+        if let Some(at) = discovery.indexing.iter().position(|f| *f == file) {
+            discovery.indexing.remove(at);
+            discovery.faces.extend(faces);
+        }
+    }
+    discovery.cache.clear();
+}
+
+/// The codepoints a face maps to glyphs, as sorted, inclusive ranges.
+fn coverage(face: &owned_ttf_parser::Face) -> Vec<(u32, u32)> {
+    let mut codepoints = Vec::new();
+    if let Some(cmap) = face.tables().cmap {
+        for subtable in cmap.subtables {
+            if subtable.is_unicode() {
+                subtable.codepoints(|c| codepoints.push(c));
+            }
+        }
+    }
+    codepoints.sort_unstable();
+    codepoints.dedup();
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for c in codepoints {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == c => *end = c,
+            _ => ranges.push((c, c)),
+        }
+    }
+    ranges
+}
+
+fn lock() -> MutexGuard<'static, Discovery> {
+    DISCOVERY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Turns finding fallbacks in the FontDB on or off, indexing the fonts
+/// loaded so far when it's turned on.
+pub fn set_enabled(enabled: bool) {
+    lock().enabled = enabled;
+    index_pending();
+}
+
+/// Indexes a font file, or the font files in a directory and its
+/// subdirectories, loaded into the FontDB. Files are indexed as they're
+/// loaded, or once finding fallbacks is turned on, so text is never drawn
+/// waiting for them.
+pub fn add_path(name: Option<&str>, path: &Path) {
+    let mut files = Vec::new();
+    font_files(path, &mut files);
+
+    {
+        let mut discovery = lock();
+        discovery.pending.extend(
+            files
+                .into_iter()
+                .map(|file| (name.map(str::to_string), file)),
+        );
+        discovery.cache.clear();
+    }
+    index_pending();
+}
+
+/// Indexes a font loaded into the FontDB from bytes.
+pub fn add_font(name: &str, font: rusttype::Font<'static>) {
+    let face = Face::new(
+        Some(name.to_string()),
+        None,
+        0,
+        face(&font),
+        Some(font.clone()),
+    );
+
+    let mut discovery = lock();
+    discovery.faces.push(face);
+    discovery.cache.clear();
+}
+
+/// Indexes the fonts in the directories system fonts are installed to.
+pub fn add_system_fonts() {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+        PathBuf::from("/System/Library/Fonts"),
+        PathBuf::from("/Library/Fonts"),
+    ];
+    if let Some(home) = home {
+        dirs.push(home.join(".fonts"));
+        dirs.push(home.join(".local/share/fonts"));
+        dirs.push(home.join("Library/Fonts"));
+    }
+    if let Some(windir) = std::env::var_os("WINDIR") {
+        dirs.push(PathBuf::from(windir).join("Fonts"));
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        dirs.push(PathBuf::from(local).join("Microsoft/Windows/Fonts"));
+    }
+
+    for dir in dirs {
+        add_path(None, &dir);
+    }
+}
+
+/// Stops finding fallbacks in the font removed from the FontDB by `name`.
+pub fn remove(name: &str) {
+    let mut discovery = lock();
+    discovery
+        .pending
+        .retain(|(pending, _)| pending.as_deref() != Some(name));
+    discovery
+        .indexing
+        .retain(|(indexing, _)| indexing.as_deref() != Some(name));
+    discovery
+        .faces
+        .retain(|face| face.name.as_deref() != Some(name));
+    discovery.cache.clear();
+}
+
+fn font_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            font_files(&entry.path(), files);
+        }
+    } else if path.extension().is_some_and(|ext| {
+        FONT_EXTENSIONS
+            .iter()
+            .any(|font_ext| ext.eq_ignore_ascii_case(font_ext))
+    }) {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// The font with fallbacks from the FontDB for the characters of `text` that
+/// none of its fonts have glyphs for, or None when finding fallbacks is
+/// turned off or there are none to add. Emojis drawn as images need no
+/// fonts when `draw_emojis` is on.
+pub fn extend(
+    font: &SuperFont<'static>,
+    text: &str,
+    draw_emojis: bool,
+) -> Option<SuperFont<'static>> {
+    if !lock().enabled {
+        return None;
+    }
+
+    let text = if draw_emojis {
+        let (text, _) = imagetext::emoji::parse::parse_out_emojis(
+            text,
+            font.emoji_options.parse_shortcodes,
+            font.emoji_options.parse_discord_emojis,
+        );
+        text.replace(EMOJI_PLACEHOLDER, "")
+    } else {
+        text.to_string()
+    };
+
+    let mut seen = BTreeSet::new();
+    let missing: Vec<char> = text
+        .chars()
+        .filter(|&c| !c.is_control() && !c.is_whitespace() && seen.insert(c))
+        .filter(|&c| {
+            std::iter::once(&font.main)
+                .chain(&font.fallbacks)
+                .all(|font| font.glyph(c).id().0 == 0)
+        })
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let style = Style::of(face(&font.main));
+    // the fonts of the picked faces, or where to read them from
+    let picked: Vec<_> = {
+        let mut discovery = lock();
+        discovery
+            .fallbacks(&missing, style)
+            .into_iter()
+            .map(|i| {
+                let face = &discovery.faces[i];
+                face.font.clone().ok_or((face.path.clone(), face.index))
+            })
+            .collect()
+    };
+
+    let found: Vec<_> = picked
+        .into_iter()
+        .filter_map(|picked| match picked {
+            Ok(font) => Some(font),
+            Err((path, index)) => load(path, index),
+        })
+        .collect();
+    if found.is_empty() {
+        return None;
+    }
+
+    Some(SuperFont::with_emoji_options(
+        font.main.clone(),
+        font.fallbacks.iter().cloned().chain(found).collect(),
+        font.emoji_options.clone(),
+    ))
+}
+
+/// Reads the font of a face picked as a fallback, keeping it with the face
+/// for the next time it's picked, or dropping the face when it fails to load.
+fn load(path: Option<PathBuf>, index: u32) -> Option<rusttype::Font<'static>> {
+    let font = path
+        .as_ref()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|data| font_from_vec(data, index).ok());
+
+    let mut discovery = lock();
+    let faces = discovery
+        .faces
+        .iter_mut()
+        .filter(|face| face.path == path && face.index == index);
+    for face in faces {
+        match &font {
+            Some(font) => face.font = Some(font.clone()),
+            None => face.ranges.clear(),
+        }
+    }
+    if font.is_none() {
+        discovery.cache.clear();
+    }
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{font, FONT_PATH};
+
+    fn synthetic(ranges: &[(char, char)], weight: u16) -> Face {
+        let ranges: Vec<_> = ranges.iter().map(|&(s, e)| (s as u32, e as u32)).collect();
+        Face {
+            name: None,
+            path: None,
+            index: 0,
+            font: None,
+            scripts: script_counts(&ranges),
+            ranges,
+            style: Style {
+                italic: false,
+                weight,
+            },
+        }
+    }
+
+    const REGULAR: Style = Style {
+        italic: false,
+        weight: 400,
+    };
+
+    #[test]
+    fn faces_cover_their_cmap() {
+        let font = font();
+        let face = Face::new(None, None, 0, face(&font), None);
+        assert!(face.covers('a') && face.covers('Z'));
+        assert!(!face.covers('漢') && !face.covers('\u{10FFFF}'));
+        assert!(face.ranges.windows(2).all(|r| r[0].1 + 1 < r[1].0));
+        assert!(face.script_coverage(Script::Latin) >= 52);
+        assert_eq!(face.script_coverage(Script::Han), 0);
+    }
+
+    #[test]
+    fn scripts_are_counted_while_indexing() {
+        let counts = script_counts(&[('a' as u32, 'z' as u32), (0x5d0, 0x5d4)]);
+        assert_eq!(counts, [(Script::Latin, 26), (Script::Hebrew, 5)]);
+    }
+
+    #[test]
+    fn collections_index_the_faces_in_the_fontdb() {
+        let path = std::env::temp_dir().join("imagetext_py_discovery.ttc");
+        std::fs::write(&path, crate::testing::collection(2)).unwrap();
+        let faces = index_file(None, &path);
+        let indices: Vec<_> = faces.iter().map(|face| face.index).collect();
+        assert_eq!(indices, [0, 1]);
+        assert!(faces.iter().all(|face| face.covers('a')));
+
+        // the FontDB loads named files by their first face
+        let faces = index_file(Some("name".to_string()), &path);
+        let indices: Vec<_> = faces.iter().map(|face| face.index).collect();
+        assert_eq!(indices, [0]);
+
+        assert_eq!(index_file(None, Path::new(FONT_PATH)).len(), 1);
+        assert!(index_file(None, Path::new("missing.ttf")).is_empty());
+    }
+
+    #[test]
+    fn paths_are_indexed_as_they_are_loaded() {
+        let name = "imagetext_py_discovery_loaded";
+        set_enabled(true);
+        add_path(Some(name), Path::new(FONT_PATH));
+        let indexed = |discovery: &Discovery| {
+            discovery
+                .faces
+                .iter()
+                .filter(|face| face.name.as_deref() == Some(name))
+                .count()
+        };
+        {
+            let discovery = lock();
+            assert_eq!(indexed(&discovery), 1);
+            assert!(discovery.pending.is_empty() && discovery.indexing.is_empty());
+        }
+
+        remove(name);
+        set_enabled(false);
+        assert_eq!(indexed(&lock()), 0);
+    }
+
+    #[test]
+    fn picks_prefer_script_coverage_then_style() {
+        let mut discovery = Discovery::new();
+        discovery.faces = vec![
+            synthetic(&[('a', 'c')], 400),
+            synthetic(&[('a', 'z')], 700),
+            synthetic(&[('a', 'y')], 400),
+            synthetic(&[('א', 'ת')], 400),
+        ];
+        // faces covering at least half as much of the script as the best
+        // are picked by style
+        assert_eq!(discovery.pick('b', REGULAR), Some(2));
+        assert_eq!(discovery.pick('z', REGULAR), Some(1));
+        assert_eq!(discovery.pick('ב', REGULAR), Some(3));
+        assert_eq!(discovery.pick('漢', REGULAR), None);
+
+        // one face per missing script, each drawing something
+        let picked = discovery.fallbacks(&['b', 'z', 'ב', '漢'], REGULAR);
+        assert_eq!(picked, [2, 3, 1]);
+        assert_eq!(discovery.cache.get(&('漢', REGULAR)), Some(&None));
+    }
+}
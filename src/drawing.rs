@@ -1,9 +1,19 @@
 use pyo3::prelude::*;
 
-use crate::canvas;
+use crate::bidi;
+use crate::canvas::{self, with_rgba, Image};
+use crate::decoration::Decoration;
+use crate::effects::Shadow;
 use crate::font::Font;
-use crate::objects::TextAlign;
+use crate::layout::{layout_line, layout_lines, Block, Spacing};
+use crate::markup::Markup;
+use crate::objects::{BackgroundMode, Direction, Overflow, Padding, TextAlign};
 use crate::paint::Paint;
+use crate::render::{self, Matrix};
+use crate::rich::{self, layout_spans, Span};
+use crate::shaping::{Features, Shaper};
+use crate::stroke::{self, StrokeArg};
+use crate::utils::{fit_lines, truncate_lines, wrap_lines, DEFAULT_ELLIPSIS};
 
 use imagetext::prelude::*;
 
@@ -17,66 +27,111 @@ pub fn draw_text(
     size: f32,
     font: &Font,
     fill: &Paint,
-    stroke: Option<f32>,
+    stroke: Option<StrokeArg>,
     stroke_color: Option<&Paint>,
     draw_emojis: Option<bool>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    shaping: Option<bool>,
+    features: Option<Features>,
 ) -> PyResult<()> {
     fn draw_text_inner(
-        im: &mut image::RgbaImage,
+        im: &mut Image,
         text: &str,
         x: f32,
         y: f32,
         size: f32,
         font: &Font,
         fill: &Paint,
-        stroke: Option<f32>,
+        stroke: Option<StrokeArg>,
         stroke_color: Option<&Paint>,
         draw_emojis: Option<bool>,
+        rotation: Option<f32>,
+        transform: Option<Matrix>,
+        shadow: Option<&Shadow>,
+        glow: Option<&Shadow>,
+        outlines: Option<Vec<(StrokeArg, Paint)>>,
+        spacing: Spacing,
+        decorations: Option<Vec<Decoration>>,
+        shaper: Option<Shaper>,
     ) -> PyResult<()> {
-        let stroke = stroke.map(imagetext::drawing::utils::stroke);
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let stroke = stroke.map(StrokeArg::into_stroke);
         let outline = match &stroke {
             Some(stroke) => Outline::Solid {
-                stroke,
+                stroke: &stroke.stroke,
                 fill: stroke_color.map(|c| &c.0).unwrap_or(&BLACK),
             },
             None => Outline::None,
         };
 
-        if draw_emojis.unwrap_or(false) {
-            imagetext::drawing::text::draw_text_mut_with_emojis(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                scale(size),
-                &font.0,
-                DefaultEmojiResolver::<true>,
+        let outlines = stroke::outline_layers(outlines);
+        let decorations = decorations.unwrap_or_default();
+        let style = text_style(
+            font,
+            fill,
+            stroke.as_ref(),
+            stroke_color,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            &outlines,
+            &decorations,
+        );
+
+        if !style.is_plain() || !spacing.is_none() || shaper.is_some() {
+            let scale = scale(size);
+            let lines = vec![layout_line(
                 text,
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+                &font.0,
+                scale,
+                draw_emojis.unwrap_or(false),
+                spacing,
+                shaper.as_ref(),
+                Direction::Auto,
+            )];
+            let block = Block::new(lines, 1.0, &TextAlign::Left, None);
+
+            return render::draw_block(im, &block, x, y, 0.0, 0.0, &style).map_err(draw_error);
+        }
+
+        if draw_emojis.unwrap_or(false) {
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_mut_with_emojis(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    scale(size),
+                    &font.0,
+                    DefaultEmojiResolver::<true>,
+                    text,
+                )
             })
+            .map_err(draw_error)
         } else {
-            imagetext::drawing::text::draw_text_mut(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                scale(size),
-                &font.0,
-                text,
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_mut(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    scale(size),
+                    &font.0,
+                    text,
+                )
             })
+            .map_err(draw_error)
         }
     }
 
@@ -92,11 +147,16 @@ pub fn draw_text(
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            Spacing::new(letter_spacing, word_spacing),
+            decorations,
+            Shaper::new(shaping, &font.1, features.as_ref()),
         ),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to draw text: {}",
-            e
-        ))),
+        Err(e) => Err(draw_error(e)),
     })
 }
 
@@ -112,12 +172,22 @@ pub fn draw_text_anchored(
     size: f32,
     font: &Font,
     fill: &Paint,
-    stroke: Option<f32>,
+    stroke: Option<StrokeArg>,
     stroke_color: Option<&Paint>,
     draw_emojis: Option<bool>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    shaping: Option<bool>,
+    features: Option<Features>,
 ) -> PyResult<()> {
     fn draw_text_anchored_inner(
-        im: &mut image::RgbaImage,
+        im: &mut Image,
         text: &str,
         x: f32,
         y: f32,
@@ -126,58 +196,93 @@ pub fn draw_text_anchored(
         size: f32,
         font: &Font,
         fill: &Paint,
-        stroke: Option<f32>,
+        stroke: Option<StrokeArg>,
         stroke_color: Option<&Paint>,
         draw_emojis: Option<bool>,
+        rotation: Option<f32>,
+        transform: Option<Matrix>,
+        shadow: Option<&Shadow>,
+        glow: Option<&Shadow>,
+        outlines: Option<Vec<(StrokeArg, Paint)>>,
+        spacing: Spacing,
+        decorations: Option<Vec<Decoration>>,
+        shaper: Option<Shaper>,
     ) -> PyResult<()> {
-        let stroke = stroke.map(imagetext::drawing::utils::stroke);
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let stroke = stroke.map(StrokeArg::into_stroke);
         let outline = match &stroke {
             Some(stroke) => Outline::Solid {
-                stroke,
+                stroke: &stroke.stroke,
                 fill: stroke_color.map(|c| &c.0).unwrap_or(&BLACK),
             },
             None => Outline::None,
         };
 
-        if draw_emojis.unwrap_or(false) {
-            imagetext::drawing::text::draw_text_anchored_with_emojis(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
-                scale(size),
-                &font.0,
-                DefaultEmojiResolver::<true>,
+        let outlines = stroke::outline_layers(outlines);
+        let decorations = decorations.unwrap_or_default();
+        let style = text_style(
+            font,
+            fill,
+            stroke.as_ref(),
+            stroke_color,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            &outlines,
+            &decorations,
+        );
+
+        if !style.is_plain() || !spacing.is_none() || shaper.is_some() {
+            let scale = scale(size);
+            let lines = vec![layout_line(
                 text,
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+                &font.0,
+                scale,
+                draw_emojis.unwrap_or(false),
+                spacing,
+                shaper.as_ref(),
+                Direction::Auto,
+            )];
+            let block = Block::new(lines, 1.0, &TextAlign::Left, None);
+
+            return render::draw_block(im, &block, x, y, ax, ay, &style).map_err(draw_error);
+        }
+
+        if draw_emojis.unwrap_or(false) {
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_anchored_with_emojis(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    ax,
+                    ay,
+                    scale(size),
+                    &font.0,
+                    DefaultEmojiResolver::<true>,
+                    text,
+                )
             })
+            .map_err(draw_error)
         } else {
-            imagetext::drawing::text::draw_text_anchored(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
-                scale(size),
-                &font.0,
-                text,
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_anchored(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    ax,
+                    ay,
+                    scale(size),
+                    &font.0,
+                    text,
+                )
             })
+            .map_err(draw_error)
         }
     }
 
@@ -195,11 +300,16 @@ pub fn draw_text_anchored(
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            Spacing::new(letter_spacing, word_spacing),
+            decorations,
+            Shaper::new(shaping, &font.1, features.as_ref()),
         ),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to draw text: {}",
-            e
-        ))),
+        Err(e) => Err(draw_error(e)),
     })
 }
 
@@ -218,12 +328,29 @@ pub fn draw_text_multiline(
     fill: &Paint,
     line_spacing: Option<f32>,
     align: Option<&TextAlign>,
-    stroke: Option<f32>,
+    stroke: Option<StrokeArg>,
     stroke_color: Option<&Paint>,
     draw_emojis: Option<bool>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    markup: Option<&Markup>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    background: Option<&Paint>,
+    padding: Option<Padding>,
+    corner_radius: Option<f32>,
+    background_mode: Option<&BackgroundMode>,
+    highlights: Option<Vec<(usize, usize, Paint)>>,
+    shaping: Option<bool>,
+    direction: Option<&Direction>,
+    features: Option<Features>,
 ) -> PyResult<()> {
     fn draw_text_multiline_inner(
-        im: &mut image::RgbaImage,
+        im: &mut Image,
         lines: Vec<String>,
         x: f32,
         y: f32,
@@ -235,65 +362,109 @@ pub fn draw_text_multiline(
         fill: &Paint,
         line_spacing: Option<f32>,
         align: Option<&TextAlign>,
-        stroke: Option<f32>,
+        stroke: Option<StrokeArg>,
         stroke_color: Option<&Paint>,
         draw_emojis: Option<bool>,
+        rotation: Option<f32>,
+        transform: Option<Matrix>,
+        shadow: Option<&Shadow>,
+        glow: Option<&Shadow>,
+        outlines: Option<Vec<(StrokeArg, Paint)>>,
+        markup: Option<&Markup>,
+        spacing: Spacing,
+        decorations: Option<Vec<Decoration>>,
+        background: Option<&Paint>,
+        padding: Option<Padding>,
+        corner_radius: Option<f32>,
+        background_mode: Option<&BackgroundMode>,
+        highlights: Option<Vec<(usize, usize, Paint)>>,
+        shaper: Option<Shaper>,
+        direction: Direction,
     ) -> PyResult<()> {
-        let stroke = stroke.map(imagetext::drawing::utils::stroke);
-        let outline = match &stroke {
-            Some(stroke) => Outline::Solid {
-                stroke,
-                fill: stroke_color.map(|c| &c.0).unwrap_or(&BLACK),
-            },
-            None => Outline::None,
+        let discovered = font.discover(&lines.concat(), draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let stroke = stroke.map(StrokeArg::into_stroke);
+        let outlines = stroke::outline_layers(outlines);
+        let decorations = decorations.unwrap_or_default();
+        let highlights = highlights.unwrap_or_default();
+        let style = render::TextStyle {
+            background: background.map(|paint| render::Background {
+                paint: &paint.0,
+                padding: padding.as_ref().map_or((0.0, 0.0), Padding::to_axes),
+                corner_radius: corner_radius.unwrap_or(0.0),
+                mode: background_mode.copied().unwrap_or(BackgroundMode::Line),
+            }),
+            highlights: render::run_highlights(&highlights, [usize::MAX]),
+            ..text_style(
+                font,
+                fill,
+                stroke.as_ref(),
+                stroke_color,
+                rotation,
+                transform,
+                shadow,
+                glow,
+                &outlines,
+                &decorations,
+            )
         };
 
-        if draw_emojis.unwrap_or(false) {
-            imagetext::drawing::text::draw_text_multiline_with_emojis(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
-                width,
-                scale(size),
+        if let Some(markup) = markup {
+            let spans = markup.parse(
+                &lines.join("\n"),
                 &font.0,
-                DefaultEmojiResolver::<true>,
-                &lines,
-                line_spacing.unwrap_or(1.0),
-                align.unwrap_or(&TextAlign::Left).to_align(),
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
-            })
-        } else {
-            imagetext::drawing::text::draw_text_multiline(
-                im,
+                size,
                 &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
-                width,
-                scale(size),
-                &font.0,
-                &lines,
+                stroke.as_ref(),
+                stroke_color.map(|c| &c.0),
+            )?;
+            let spans =
+                rich::discover_fallbacks(&spans, draw_emojis.unwrap_or(false)).unwrap_or(spans);
+            let lines = layout_spans(
+                &spans,
+                None,
+                draw_emojis.unwrap_or(false),
+                crate::objects::WrapStyle::Word,
+                spacing,
+                shaper.as_ref(),
+                direction,
+            );
+            let block = Block::new(
+                lines,
                 line_spacing.unwrap_or(1.0),
-                align.unwrap_or(&TextAlign::Left).to_align(),
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
-            })
+                align.unwrap_or(&TextAlign::Left),
+                Some(width),
+            );
+            let style = render::TextStyle {
+                runs: spans.iter().map(Span::style).collect(),
+                highlights: render::run_highlights(
+                    &highlights,
+                    spans.iter().map(|span| span.text.chars().count()),
+                ),
+                ..style
+            };
+
+            return render::draw_block(im, &block, x, y, ax, ay, &style).map_err(draw_error);
         }
+
+        draw_lines(
+            im,
+            &lines,
+            x,
+            y,
+            ax,
+            ay,
+            width,
+            size,
+            font,
+            &style,
+            line_spacing.unwrap_or(1.0),
+            align.unwrap_or(&TextAlign::Left),
+            draw_emojis.unwrap_or(false),
+            spacing,
+            shaper.as_ref(),
+            direction,
+        )
     }
 
     py.allow_threads(|| match canvas.0.write() {
@@ -313,14 +484,153 @@ pub fn draw_text_multiline(
             stroke,
             stroke_color,
             draw_emojis,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            markup,
+            Spacing::new(letter_spacing, word_spacing),
+            decorations,
+            background,
+            padding,
+            corner_radius,
+            background_mode,
+            highlights,
+            Shaper::new(shaping, &font.1, features.as_ref()),
+            direction.copied().unwrap_or(Direction::Auto),
         ),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to draw text: {}",
-            e
-        ))),
+        Err(e) => Err(draw_error(e)),
     })
 }
 
+/// The error raised when drawing fails or the canvas lock is poisoned.
+fn draw_error(e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to draw text: {}", e))
+}
+
+/// The style of text drawn as a single run, shared by every function drawing
+/// text in one font so their options draw the same.
+fn text_style<'a>(
+    font: &'a Font,
+    fill: &'a Paint,
+    stroke: Option<&'a stroke::Stroke>,
+    stroke_color: Option<&'a Paint>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&'a Shadow>,
+    glow: Option<&'a Shadow>,
+    outlines: &'a [(stroke::Stroke, imagetext::prelude::Paint<'static>)],
+    decorations: &'a [Decoration],
+) -> render::TextStyle<'a> {
+    render::TextStyle {
+        runs: vec![render::RunStyle {
+            font: &font.0,
+            fill: &fill.0,
+            outline: stroke.map(|s| (s, stroke_color.map(|c| &c.0).unwrap_or(&BLACK))),
+        }],
+        transform: render::user_transform(rotation, transform),
+        shadow,
+        glow,
+        outlines,
+        decorations,
+        background: None,
+        highlights: Vec::new(),
+        fade: None,
+    }
+}
+
+/// Draws lines of text in the single run of `style`, with imagetext when the
+/// style and layout leave nothing for it to miss.
+fn draw_lines(
+    im: &mut Image,
+    lines: &[String],
+    x: f32,
+    y: f32,
+    ax: f32,
+    ay: f32,
+    width: f32,
+    size: f32,
+    font: &Font,
+    style: &render::TextStyle,
+    line_spacing: f32,
+    align: &TextAlign,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> PyResult<()> {
+    if !style.is_plain()
+        || !spacing.is_none()
+        || shaper.is_some()
+        || align.is_justified()
+        || lines.iter().any(|line| bidi::is_needed(line, direction))
+    {
+        let lines = layout_lines(
+            lines,
+            &font.0,
+            scale(size),
+            draw_emojis,
+            spacing,
+            shaper,
+            direction,
+        );
+        let block = Block::new(lines, line_spacing, align, Some(width));
+
+        return render::draw_block(im, &block, x, y, ax, ay, style).map_err(draw_error);
+    }
+
+    let run = &style.runs[0];
+    let outline = match run.outline {
+        Some((stroke, fill)) => Outline::Solid {
+            stroke: &stroke.stroke,
+            fill,
+        },
+        None => Outline::None,
+    };
+
+    if draw_emojis {
+        with_rgba(im, |im| {
+            imagetext::drawing::text::draw_text_multiline_with_emojis(
+                im,
+                run.fill,
+                outline,
+                x,
+                y,
+                ax,
+                ay,
+                width,
+                scale(size),
+                &font.0,
+                DefaultEmojiResolver::<true>,
+                lines,
+                line_spacing,
+                align.to_align(),
+            )
+        })
+        .map_err(draw_error)
+    } else {
+        with_rgba(im, |im| {
+            imagetext::drawing::text::draw_text_multiline(
+                im,
+                run.fill,
+                outline,
+                x,
+                y,
+                ax,
+                ay,
+                width,
+                scale(size),
+                &font.0,
+                lines,
+                line_spacing,
+                align.to_align(),
+            )
+        })
+        .map_err(draw_error)
+    }
+}
+
 #[pyfunction]
 pub fn draw_text_wrapped(
     py: Python,
@@ -336,13 +646,33 @@ pub fn draw_text_wrapped(
     fill: &Paint,
     line_spacing: Option<f32>,
     align: Option<&TextAlign>,
-    stroke: Option<f32>,
+    stroke: Option<StrokeArg>,
     stroke_color: Option<&Paint>,
     draw_emojis: Option<bool>,
     wrap_style: Option<&crate::objects::WrapStyle>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    markup: Option<&Markup>,
+    max_lines: Option<usize>,
+    overflow: Option<&Overflow>,
+    ellipsis: Option<&str>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    background: Option<&Paint>,
+    padding: Option<Padding>,
+    corner_radius: Option<f32>,
+    background_mode: Option<&BackgroundMode>,
+    highlights: Option<Vec<(usize, usize, Paint)>>,
+    shaping: Option<bool>,
+    direction: Option<&Direction>,
+    features: Option<Features>,
 ) -> PyResult<()> {
     fn draw_text_wrapped_inner(
-        im: &mut image::RgbaImage,
+        im: &mut Image,
         text: &str,
         x: f32,
         y: f32,
@@ -354,71 +684,302 @@ pub fn draw_text_wrapped(
         fill: &Paint,
         line_spacing: Option<f32>,
         align: Option<&TextAlign>,
-        stroke: Option<f32>,
+        stroke: Option<StrokeArg>,
         stroke_color: Option<&Paint>,
         draw_emojis: Option<bool>,
         wrap_style: Option<&crate::objects::WrapStyle>,
+        rotation: Option<f32>,
+        transform: Option<Matrix>,
+        shadow: Option<&Shadow>,
+        glow: Option<&Shadow>,
+        outlines: Option<Vec<(StrokeArg, Paint)>>,
+        markup: Option<&Markup>,
+        max_lines: Option<usize>,
+        overflow: Option<&Overflow>,
+        ellipsis: Option<&str>,
+        spacing: Spacing,
+        decorations: Option<Vec<Decoration>>,
+        background: Option<&Paint>,
+        padding: Option<Padding>,
+        corner_radius: Option<f32>,
+        background_mode: Option<&BackgroundMode>,
+        highlights: Option<Vec<(usize, usize, Paint)>>,
+        shaper: Option<Shaper>,
+        direction: Direction,
     ) -> PyResult<()> {
-        let stroke = stroke.map(imagetext::drawing::utils::stroke);
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let stroke = stroke.map(StrokeArg::into_stroke);
         let outline = match &stroke {
             Some(stroke) => Outline::Solid {
-                stroke,
+                stroke: &stroke.stroke,
                 fill: stroke_color.map(|c| &c.0).unwrap_or(&BLACK),
             },
             None => Outline::None,
         };
 
-        if draw_emojis.unwrap_or(false) {
-            imagetext::drawing::text::draw_text_wrapped_with_emojis(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
+        let wrapped = max_lines.map(|max_lines| {
+            let lines = wrap_lines(
+                text,
+                width as i32,
+                size,
+                font,
+                draw_emojis,
+                wrap_style.copied(),
+                spacing,
+                shaper.as_ref(),
+                direction,
+            );
+            truncate_lines(
+                lines,
+                max_lines,
+                overflow.copied(),
+                ellipsis,
                 width,
-                scale(size),
-                &font.0,
-                DefaultEmojiResolver::<true>,
+                size,
+                font,
+                draw_emojis,
+                spacing,
+                shaper.as_ref(),
+                direction,
+            )
+        });
+        // faded lines fade out over their last two ems
+        let fade = |truncated: bool| {
+            (truncated && overflow == Some(&Overflow::Fade)).then_some(size * 2.0)
+        };
+
+        let outlines = stroke::outline_layers(outlines);
+        let decorations = decorations.unwrap_or_default();
+        let highlights = highlights.unwrap_or_default();
+        let style = render::TextStyle {
+            background: background.map(|paint| render::Background {
+                paint: &paint.0,
+                padding: padding.as_ref().map_or((0.0, 0.0), Padding::to_axes),
+                corner_radius: corner_radius.unwrap_or(0.0),
+                mode: background_mode.copied().unwrap_or(BackgroundMode::Line),
+            }),
+            highlights: render::run_highlights(&highlights, [usize::MAX]),
+            fade: fade(wrapped.as_ref().is_some_and(|(_, truncated)| *truncated)),
+            ..text_style(
+                font,
+                fill,
+                stroke.as_ref(),
+                stroke_color,
+                rotation,
+                transform,
+                shadow,
+                glow,
+                &outlines,
+                &decorations,
+            )
+        };
+
+        let spans = match markup {
+            Some(markup) => Some(markup.parse(
                 text,
-                line_spacing.unwrap_or(1.0),
-                align.unwrap_or(&TextAlign::Left).to_align(),
+                &font.0,
+                size,
+                &fill.0,
+                stroke.as_ref(),
+                stroke_color.map(|c| &c.0),
+            )?),
+            // highlights index into the text and bidi runs over paragraphs,
+            // wrapped lines lose track of both
+            None if !highlights.is_empty() || bidi::is_needed(text, direction) => {
+                Some(vec![Span {
+                    text: text.to_string(),
+                    font: font.0.clone(),
+                    size,
+                    fill: fill.0.clone(),
+                    stroke: stroke.clone(),
+                    stroke_color: stroke_color.map(|c| c.0.clone()),
+                    features: font.1.clone(),
+                }])
+            }
+            None => None,
+        }
+        .map(|spans| {
+            rich::discover_fallbacks(&spans, draw_emojis.unwrap_or(false)).unwrap_or(spans)
+        });
+
+        if let Some(spans) = spans {
+            let mut lines = layout_spans(
+                &spans,
+                Some(width),
+                draw_emojis.unwrap_or(false),
                 wrap_style
-                    .unwrap_or(&crate::objects::WrapStyle::Word)
-                    .to_wrap_style(),
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+                    .copied()
+                    .unwrap_or(crate::objects::WrapStyle::Word),
+                spacing,
+                shaper.as_ref(),
+                direction,
+            );
+            let truncated = max_lines.is_some_and(|max_lines| {
+                rich::truncate_lines(
+                    &mut lines,
+                    &spans,
+                    max_lines,
+                    overflow.copied(),
+                    ellipsis.unwrap_or(DEFAULT_ELLIPSIS),
+                    Some(width),
+                    draw_emojis.unwrap_or(false),
+                    spacing,
+                    shaper.as_ref(),
+                )
+            });
+            let block = Block::new(
+                lines,
+                line_spacing.unwrap_or(1.0),
+                align.unwrap_or(&TextAlign::Left),
+                Some(width),
+            );
+            let style = render::TextStyle {
+                runs: spans.iter().map(Span::style).collect(),
+                highlights: render::run_highlights(
+                    &highlights,
+                    spans.iter().map(|span| span.text.chars().count()),
+                ),
+                fade: fade(truncated),
+                ..style
+            };
+
+            return render::draw_block(im, &block, x, y, ax, ay, &style).map_err(draw_error);
+        }
+
+        if !style.is_plain()
+            || !spacing.is_none()
+            || shaper.is_some()
+            || align.is_some_and(TextAlign::is_justified)
+        {
+            let scale = scale(size);
+            let lines = match wrapped {
+                Some((lines, _)) => lines,
+                None => wrap_lines(
+                    text,
+                    width as i32,
+                    size,
+                    font,
+                    draw_emojis,
+                    wrap_style.copied(),
+                    spacing,
+                    shaper.as_ref(),
+                    direction,
+                ),
+            };
+            let lines = lines
+                .iter()
+                .map(|line| {
+                    layout_line(
+                        line,
+                        &font.0,
+                        scale,
+                        draw_emojis.unwrap_or(false),
+                        spacing,
+                        shaper.as_ref(),
+                        direction,
+                    )
+                })
+                .collect();
+            let block = Block::new(
+                lines,
+                line_spacing.unwrap_or(1.0),
+                align.unwrap_or(&TextAlign::Left),
+                Some(width),
+            );
+
+            return render::draw_block(im, &block, x, y, ax, ay, &style).map_err(draw_error);
+        }
+
+        // imagetext wraps by itself, truncated lines are drawn as they are
+        if let Some((lines, _)) = wrapped {
+            let result = if draw_emojis.unwrap_or(false) {
+                with_rgba(im, |im| {
+                    imagetext::drawing::text::draw_text_multiline_with_emojis(
+                        im,
+                        &fill.0,
+                        outline,
+                        x,
+                        y,
+                        ax,
+                        ay,
+                        width,
+                        scale(size),
+                        &font.0,
+                        DefaultEmojiResolver::<true>,
+                        &lines,
+                        line_spacing.unwrap_or(1.0),
+                        align.unwrap_or(&TextAlign::Left).to_align(),
+                    )
+                })
+            } else {
+                with_rgba(im, |im| {
+                    imagetext::drawing::text::draw_text_multiline(
+                        im,
+                        &fill.0,
+                        outline,
+                        x,
+                        y,
+                        ax,
+                        ay,
+                        width,
+                        scale(size),
+                        &font.0,
+                        &lines,
+                        line_spacing.unwrap_or(1.0),
+                        align.unwrap_or(&TextAlign::Left).to_align(),
+                    )
+                })
+            };
+
+            return result.map_err(draw_error);
+        }
+
+        if draw_emojis.unwrap_or(false) {
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_wrapped_with_emojis(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    ax,
+                    ay,
+                    width,
+                    scale(size),
+                    &font.0,
+                    DefaultEmojiResolver::<true>,
+                    text,
+                    line_spacing.unwrap_or(1.0),
+                    align.unwrap_or(&TextAlign::Left).to_align(),
+                    wrap_style
+                        .unwrap_or(&crate::objects::WrapStyle::Word)
+                        .to_wrap_style(),
+                )
             })
+            .map_err(draw_error)
         } else {
-            imagetext::drawing::text::draw_text_wrapped(
-                im,
-                &fill.0,
-                outline,
-                x,
-                y,
-                ax,
-                ay,
-                width,
-                scale(size),
-                &font.0,
-                text,
-                line_spacing.unwrap_or(1.0),
-                align.unwrap_or(&TextAlign::Left).to_align(),
-                wrap_style
-                    .unwrap_or(&crate::objects::WrapStyle::Word)
-                    .to_wrap_style(),
-            )
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to draw text: {}",
-                    e
-                ))
+            with_rgba(im, |im| {
+                imagetext::drawing::text::draw_text_wrapped(
+                    im,
+                    &fill.0,
+                    outline,
+                    x,
+                    y,
+                    ax,
+                    ay,
+                    width,
+                    scale(size),
+                    &font.0,
+                    text,
+                    line_spacing.unwrap_or(1.0),
+                    align.unwrap_or(&TextAlign::Left).to_align(),
+                    wrap_style
+                        .unwrap_or(&crate::objects::WrapStyle::Word)
+                        .to_wrap_style(),
+                )
             })
+            .map_err(draw_error)
         }
     }
 
@@ -440,10 +1001,232 @@ pub fn draw_text_wrapped(
             stroke_color,
             draw_emojis,
             wrap_style,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            markup,
+            max_lines,
+            overflow,
+            ellipsis,
+            Spacing::new(letter_spacing, word_spacing),
+            decorations,
+            background,
+            padding,
+            corner_radius,
+            background_mode,
+            highlights,
+            Shaper::new(shaping, &font.1, features.as_ref()),
+            direction.copied().unwrap_or(Direction::Auto),
+        ),
+        Err(e) => Err(draw_error(e)),
+    })
+}
+
+#[pyfunction]
+pub fn draw_text_fitted(
+    py: Python,
+    canvas: &mut canvas::Canvas,
+    text: &str,
+    x: f32,
+    y: f32,
+    ax: f32,
+    ay: f32,
+    width: f32,
+    height: f32,
+    font: &Font,
+    fill: &Paint,
+    min_size: Option<f32>,
+    max_size: Option<f32>,
+    line_spacing: Option<f32>,
+    align: Option<&TextAlign>,
+    stroke: Option<StrokeArg>,
+    stroke_color: Option<&Paint>,
+    draw_emojis: Option<bool>,
+    wrap_style: Option<&crate::objects::WrapStyle>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    shaping: Option<bool>,
+    direction: Option<&Direction>,
+    features: Option<Features>,
+) -> PyResult<f32> {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let direction = direction.copied().unwrap_or(Direction::Auto);
+    let stroke = stroke.map(StrokeArg::into_stroke);
+    let outlines = stroke::outline_layers(outlines);
+    let decorations = decorations.unwrap_or_default();
+
+    py.allow_threads(|| {
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+        let (size, lines) = fit_lines(
+            text,
+            font,
+            width,
+            height,
+            min_size,
+            max_size,
+            line_spacing,
+            wrap_style.copied(),
+            draw_emojis,
+            spacing,
+            shaper.as_ref(),
+            direction,
+        );
+
+        let style = text_style(
+            font,
+            fill,
+            stroke.as_ref(),
+            stroke_color,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            &outlines,
+            &decorations,
+        );
+        match canvas.0.write() {
+            Ok(mut im) => draw_lines(
+                &mut im,
+                &lines,
+                x,
+                y,
+                ax,
+                ay,
+                width,
+                size,
+                font,
+                &style,
+                line_spacing.unwrap_or(1.0),
+                align.unwrap_or(&TextAlign::Left),
+                draw_emojis.unwrap_or(false),
+                spacing,
+                shaper.as_ref(),
+                direction,
+            )
+            .map(|_| size),
+            Err(e) => Err(draw_error(e)),
+        }
+    })
+}
+
+#[pyfunction]
+pub fn draw_rich_text(
+    py: Python,
+    canvas: canvas::Canvas,
+    spans: Vec<Span>,
+    x: f32,
+    y: f32,
+    ax: f32,
+    ay: f32,
+    width: Option<f32>,
+    line_spacing: Option<f32>,
+    align: Option<&TextAlign>,
+    draw_emojis: Option<bool>,
+    wrap_style: Option<&crate::objects::WrapStyle>,
+    rotation: Option<f32>,
+    transform: Option<Matrix>,
+    shadow: Option<&Shadow>,
+    glow: Option<&Shadow>,
+    outlines: Option<Vec<(StrokeArg, Paint)>>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    decorations: Option<Vec<Decoration>>,
+    shaping: Option<bool>,
+    direction: Option<&Direction>,
+    features: Option<Features>,
+) -> PyResult<()> {
+    fn draw_rich_text_inner(
+        im: &mut Image,
+        spans: &[Span],
+        x: f32,
+        y: f32,
+        ax: f32,
+        ay: f32,
+        width: Option<f32>,
+        line_spacing: Option<f32>,
+        align: Option<&TextAlign>,
+        draw_emojis: Option<bool>,
+        wrap_style: Option<&crate::objects::WrapStyle>,
+        rotation: Option<f32>,
+        transform: Option<Matrix>,
+        shadow: Option<&Shadow>,
+        glow: Option<&Shadow>,
+        outlines: Option<Vec<(StrokeArg, Paint)>>,
+        spacing: Spacing,
+        decorations: Option<Vec<Decoration>>,
+        shaper: Option<Shaper>,
+        direction: Direction,
+    ) -> PyResult<()> {
+        let discovered = rich::discover_fallbacks(spans, draw_emojis.unwrap_or(false));
+        let spans = discovered.as_deref().unwrap_or(spans);
+        let outlines = stroke::outline_layers(outlines);
+        let decorations = decorations.unwrap_or_default();
+        let style = render::TextStyle {
+            runs: spans.iter().map(Span::style).collect(),
+            transform: render::user_transform(rotation, transform),
+            shadow,
+            glow,
+            outlines: &outlines,
+            decorations: &decorations,
+            background: None,
+            highlights: Vec::new(),
+            fade: None,
+        };
+
+        let lines = layout_spans(
+            spans,
+            width,
+            draw_emojis.unwrap_or(false),
+            wrap_style
+                .copied()
+                .unwrap_or(crate::objects::WrapStyle::Word),
+            spacing,
+            shaper.as_ref(),
+            direction,
+        );
+        let block = Block::new(
+            lines,
+            line_spacing.unwrap_or(1.0),
+            align.unwrap_or(&TextAlign::Left),
+            width,
+        );
+
+        render::draw_block(im, &block, x, y, ax, ay, &style).map_err(draw_error)
+    }
+
+    py.allow_threads(|| match canvas.0.write() {
+        Ok(mut im) => draw_rich_text_inner(
+            &mut im,
+            &spans,
+            x,
+            y,
+            ax,
+            ay,
+            width,
+            line_spacing,
+            align,
+            draw_emojis,
+            wrap_style,
+            rotation,
+            transform,
+            shadow,
+            glow,
+            outlines,
+            Spacing::new(letter_spacing, word_spacing),
+            decorations,
+            Shaper::new(shaping, &Features::default(), features.as_ref()),
+            direction.copied().unwrap_or(Direction::Auto),
         ),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to draw text: {}",
-            e
-        ))),
+        Err(e) => Err(draw_error(e)),
     })
 }
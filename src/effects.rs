@@ -0,0 +1,257 @@
+use pyo3::prelude::*;
+use tiny_skia::{IntRect, Mask, MaskType, Pixmap, PixmapMut, PixmapPaint, Rect, Transform};
+
+use crate::paint::Paint;
+
+/// A blurred copy of the text drawn beneath it.
+#[pyclass]
+#[derive(Clone)]
+pub struct Shadow {
+    pub offset: (f32, f32),
+    pub blur: f32,
+    pub paint: imagetext::prelude::Paint<'static>,
+}
+
+#[pymethods]
+impl Shadow {
+    #[new]
+    fn new(offset: Option<(f32, f32)>, blur: Option<f32>, color: Option<&Paint>) -> Self {
+        Shadow {
+            offset: offset.unwrap_or((0.0, 0.0)),
+            blur: blur.unwrap_or(0.0).max(0.0),
+            paint: color.map(|c| c.0.clone()).unwrap_or_default(),
+        }
+    }
+
+    #[getter]
+    fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    #[getter]
+    fn blur(&self) -> f32 {
+        self.blur
+    }
+}
+
+impl Shadow {
+    fn sigma(&self) -> f32 {
+        // CSS convention, the blur radius is twice the standard deviation
+        self.blur / 2.0
+    }
+
+    /// The area the shadow of coverage within `bounds` can reach, moved by
+    /// `offset`.
+    pub fn area(&self, bounds: Rect, offset: (f32, f32)) -> Option<Rect> {
+        let margin = (self.sigma() * 3.0).ceil();
+        let r = bounds.outset(margin, margin)?;
+        Rect::from_xywh(r.x() + offset.0, r.y() + offset.1, r.width(), r.height())
+    }
+}
+
+/// Sizes of the three box blurs that approximate a gaussian blur of `sigma`.
+fn box_sizes(sigma: f32) -> [usize; 3] {
+    let n = 3.0;
+    let ideal = (12.0 * sigma * sigma / n + 1.0).sqrt();
+    let mut lower = ideal.floor() as i32;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let lower = lower.max(1) as f32;
+    let upper = lower + 2.0;
+    let m = ((12.0 * sigma * sigma - n * lower * lower - 4.0 * n * lower - 3.0 * n)
+        / (-4.0 * lower - 4.0))
+        .round() as i32;
+
+    let mut sizes = [0; 3];
+    for (i, size) in sizes.iter_mut().enumerate() {
+        *size = if (i as i32) < m { lower } else { upper } as usize;
+    }
+    sizes
+}
+
+/// Blurs `count` runs of `len` values, the `j`th value of run `i` being at
+/// `i * outer + j * inner`. Values outside of the data are treated as 0.
+fn box_blur(
+    src: &[u8],
+    dst: &mut [u8],
+    count: usize,
+    len: usize,
+    outer: usize,
+    inner: usize,
+    radius: usize,
+) {
+    let diameter = (radius * 2 + 1) as u32;
+
+    for i in 0..count {
+        let at = |j: usize| src[i * outer + j * inner] as u32;
+
+        let mut sum: u32 = (0..radius.min(len)).map(at).sum();
+        for j in 0..len {
+            if j + radius < len {
+                sum += at(j + radius);
+            }
+            dst[i * outer + j * inner] = ((sum + diameter / 2) / diameter) as u8;
+            if j >= radius {
+                sum -= at(j - radius);
+            }
+        }
+    }
+}
+
+/// Applies an approximate gaussian blur to a single channel image in place.
+pub fn gaussian_blur(data: &mut [u8], width: usize, height: usize, sigma: f32) {
+    if sigma <= 0.0 || width == 0 || height == 0 {
+        return;
+    }
+
+    let mut scratch = vec![0; data.len()];
+    for size in box_sizes(sigma) {
+        let radius = (size - 1) / 2;
+        box_blur(data, &mut scratch, height, width, width, 1, radius);
+        box_blur(&scratch, data, width, height, 1, width, radius);
+    }
+}
+
+/// Draws `shadow` from the coverage `draw_coverage` paints onto a layer.
+///
+/// `bounds` is the area of the canvas the coverage can reach before the
+/// shadow's offset and blur are applied. `draw_coverage` is given the layer
+/// and the transform that maps canvas space onto it.
+pub fn draw_shadow(
+    pixmap: &mut PixmapMut,
+    shadow: &Shadow,
+    offset: (f32, f32),
+    bounds: Rect,
+    draw_coverage: impl Fn(&mut PixmapMut, Transform),
+) {
+    let sigma = shadow.sigma();
+    let area = shadow
+        .area(bounds, offset)
+        .and_then(|r| r.round_out())
+        .and_then(|r| r.intersect(&IntRect::from_xywh(0, 0, pixmap.width(), pixmap.height())?));
+
+    let (area, mut layer) = match area.and_then(|a| Some((a, Pixmap::new(a.width(), a.height())?)))
+    {
+        Some(area) => area,
+        None => return,
+    };
+
+    let to_layer = Transform::from_translate(-area.x() as f32, -area.y() as f32);
+    draw_coverage(
+        &mut layer.as_mut(),
+        to_layer.pre_translate(offset.0, offset.1),
+    );
+
+    let mut mask = Mask::from_pixmap(layer.as_ref(), MaskType::Alpha);
+    gaussian_blur(
+        mask.data_mut(),
+        area.width() as usize,
+        area.height() as usize,
+        sigma,
+    );
+
+    layer.fill(tiny_skia::Color::TRANSPARENT);
+    if let Some(rect) = Rect::from_xywh(
+        area.x() as f32,
+        area.y() as f32,
+        area.width() as f32,
+        area.height() as f32,
+    ) {
+        layer.fill_rect(rect, &shadow.paint, to_layer, Some(&mask));
+    }
+
+    pixmap.draw_pixmap(
+        area.x(),
+        area.y(),
+        layer.as_ref(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        None,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_sizes_are_odd_and_match_sigma() {
+        for sigma in [0.5, 1.0, 2.0, 5.0, 12.5] {
+            let sizes = box_sizes(sigma);
+            assert!(sizes.iter().all(|size| size % 2 == 1), "{:?}", sizes);
+
+            // the variance of n boxes of size w adds up to (w^2 - 1) / 12 each
+            let variance: f32 = sizes.iter().map(|&w| (w * w - 1) as f32 / 12.0).sum();
+            assert!(
+                (variance.sqrt() - sigma).abs() < 0.6,
+                "{} {:?}",
+                sigma,
+                sizes
+            );
+        }
+        assert_eq!(box_sizes(0.1), [1, 1, 1]);
+    }
+
+    #[test]
+    fn blur_spreads_and_keeps_the_total() {
+        let (width, height) = (21, 21);
+        let mut data = vec![0u8; width * height];
+        data[10 * width + 10] = 255;
+        data[10 * width + 9] = 255;
+        data[9 * width + 10] = 255;
+        data[9 * width + 9] = 255;
+        let before: u32 = data.iter().map(|&v| v as u32).sum();
+
+        gaussian_blur(&mut data, width, height, 2.0);
+
+        let after: u32 = data.iter().map(|&v| v as u32).sum();
+        assert!(before.abs_diff(after) < before / 10, "{} {}", before, after);
+        assert!(data[10 * width + 10] < 255 && data[10 * width + 14] > 0);
+        // symmetric around the center of the square
+        assert_eq!(data[9 * width + 4], data[10 * width + 15]);
+        assert_eq!(data[0], 0);
+    }
+
+    #[test]
+    fn shadows_are_offset_from_the_coverage() {
+        let mut pixmap = Pixmap::new(20, 10).unwrap();
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(0, 0, 255, 255);
+        let shadow = Shadow {
+            offset: (5.0, 2.0),
+            blur: 0.0,
+            paint,
+        };
+        let bounds = Rect::from_xywh(2.0, 2.0, 4.0, 4.0).unwrap();
+        let square = tiny_skia::PathBuilder::from_rect(bounds);
+
+        draw_shadow(
+            &mut pixmap.as_mut(),
+            &shadow,
+            shadow.offset,
+            bounds,
+            |layer, transform| {
+                let fill = tiny_skia::Paint::default();
+                layer.fill_path(
+                    &square,
+                    &fill,
+                    tiny_skia::FillRule::Winding,
+                    transform,
+                    None,
+                );
+            },
+        );
+
+        let blue = |x, y| pixmap.pixel(x, y).unwrap().blue();
+        assert_eq!((blue(7, 4), blue(10, 7)), (255, 255));
+        assert_eq!((blue(3, 3), blue(11, 4), blue(8, 8)), (0, 0, 0));
+    }
+
+    #[test]
+    fn no_blur_leaves_data_alone() {
+        let mut data = vec![0, 255, 0, 128];
+        gaussian_blur(&mut data, 2, 2, 0.0);
+        assert_eq!(data, [0, 255, 0, 128]);
+    }
+}
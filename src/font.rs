@@ -1,118 +1,835 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
 use imagetext::prelude::*;
+use owned_ttf_parser::{name_id, AsFaceRef, FaceMut};
 use pyo3::prelude::*;
 
+use crate::discovery;
 use crate::objects::EmojiOptions;
+use crate::shaping::{parse_tag, Features};
 
+/// A font with its fallbacks, the OpenType features text drawn with it gets
+/// by default, and the variations fallbacks added to it are drawn at.
 #[pyclass]
-pub struct Font(pub SuperFont<'static>);
+pub struct Font(pub SuperFont<'static>, pub Features, pub Variations);
 
 #[pymethods]
 impl Font {
+    /// Loads a font from its path, `index` picking the face of a font
+    /// collection.
     #[new]
     fn new(
         path: &str,
-        fallbacks: Option<Vec<String>>,
+        fallbacks: Option<Vec<FallbackArg>>,
         emoji_options: Option<EmojiOptions>,
+        features: Option<Features>,
+        variations: Option<Variations>,
+        index: Option<u32>,
     ) -> PyResult<Self> {
-        let font = load_font(path).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load font: {}", e))
-        })?;
+        let font = load_face(path, index.unwrap_or(0)).map_err(load_error)?;
+        Font::build(font, fallbacks, emoji_options, features, variations)
+    }
 
-        let fallbacks = if let Some(fallbacks) = fallbacks {
-            fallbacks
-                .into_iter()
-                .map(|path| {
-                    load_font(&path).map_err(|e| {
-                        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-                            "Failed to load font: {}",
-                            e
-                        ))
-                    })
-                })
-                .collect::<PyResult<Vec<_>>>()?
-        } else {
-            Vec::new()
+    /// Loads a font from its contents, `index` picking the face of a font
+    /// collection.
+    #[staticmethod]
+    fn from_bytes(
+        data: &[u8],
+        index: Option<u32>,
+        fallbacks: Option<Vec<FallbackArg>>,
+        emoji_options: Option<EmojiOptions>,
+        features: Option<Features>,
+        variations: Option<Variations>,
+    ) -> PyResult<Self> {
+        let font = font_from_vec(data.to_vec(), index.unwrap_or(0)).map_err(load_error)?;
+        Font::build(font, fallbacks, emoji_options, features, variations)
+    }
+
+    /// The faces of a font file, with their family and style names. Font
+    /// collections have several of them, other fonts a single one.
+    #[staticmethod]
+    fn faces(source: FontSource) -> PyResult<Vec<FaceInfo>> {
+        let data = match source {
+            FontSource::Path(path) => {
+                std::borrow::Cow::Owned(std::fs::read(path).map_err(load_error)?)
+            }
+            FontSource::Bytes(data) => std::borrow::Cow::Borrowed(data),
         };
 
-        Ok(Font(SuperFont::with_emoji_options(
+        (0..owned_ttf_parser::fonts_in_collection(&data).unwrap_or(1))
+            .map(|index| {
+                let face = owned_ttf_parser::Face::from_slice(&data, index).map_err(load_error)?;
+                Ok((
+                    index,
+                    face_name(&face, &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]),
+                    face_name(&face, &[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]),
+                ))
+            })
+            .collect()
+    }
+
+    pub fn set_emoji_options(&mut self, emoji_options: EmojiOptions) {
+        self.0.emoji_options = emoji_options.to_emoji_options();
+    }
+
+    /// The fallback fonts, each on its own without fallbacks.
+    #[getter]
+    fn fallbacks(&self) -> Vec<Font> {
+        self.0
+            .fallbacks
+            .iter()
+            .map(|fallback| {
+                Font(
+                    SuperFont::with_emoji_options(
+                        fallback.clone(),
+                        Vec::new(),
+                        self.0.emoji_options.clone(),
+                    ),
+                    Features::default(),
+                    Variations::default(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn add_fallback(&mut self, font: FallbackArg) -> PyResult<()> {
+        let font = self.fallback(font)?;
+        self.0.fallbacks.push(font);
+        Ok(())
+    }
+
+    /// Inserts a fallback before the one at `index`, like `list.insert`.
+    pub fn insert_fallback(&mut self, index: isize, font: FallbackArg) -> PyResult<()> {
+        let index = insert_index(index, self.0.fallbacks.len());
+        let font = self.fallback(font)?;
+        self.0.fallbacks.insert(index, font);
+        Ok(())
+    }
+
+    /// Removes the fallback at `index`, like `list.pop`.
+    pub fn remove_fallback(&mut self, index: isize) -> PyResult<()> {
+        let at = pop_index(index, self.0.fallbacks.len()).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyIndexError, _>(format!(
+                "Fallback index out of range: {}",
+                index
+            ))
+        })?;
+        self.0.fallbacks.remove(at);
+        Ok(())
+    }
+
+    /// A copy of the font with other fallbacks, sharing its parsed data.
+    pub fn with_fallbacks(&self, fallbacks: Vec<FallbackArg>) -> PyResult<Font> {
+        let fallbacks = fallbacks
+            .into_iter()
+            .map(|fallback| self.fallback(fallback))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(Font(
+            SuperFont::with_emoji_options(
+                self.0.main.clone(),
+                fallbacks,
+                self.0.emoji_options.clone(),
+            ),
+            self.1.clone(),
+            self.2.clone(),
+        ))
+    }
+
+    #[getter]
+    fn family(&self) -> Option<String> {
+        face_name(
+            face(&self.0.main),
+            &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY],
+        )
+    }
+
+    #[getter]
+    fn subfamily(&self) -> Option<String> {
+        face_name(
+            face(&self.0.main),
+            &[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY],
+        )
+    }
+
+    #[getter]
+    fn units_per_em(&self) -> u16 {
+        face(&self.0.main).units_per_em()
+    }
+
+    #[getter]
+    fn ascent(&self) -> i16 {
+        face(&self.0.main).ascender()
+    }
+
+    #[getter]
+    fn descent(&self) -> i16 {
+        face(&self.0.main).descender()
+    }
+
+    #[getter]
+    fn line_gap(&self) -> i16 {
+        face(&self.0.main).line_gap()
+    }
+
+    #[getter]
+    fn cap_height(&self) -> Option<i16> {
+        face(&self.0.main).capital_height()
+    }
+
+    #[getter]
+    fn x_height(&self) -> Option<i16> {
+        face(&self.0.main).x_height()
+    }
+
+    #[getter]
+    fn underline_position(&self) -> Option<i16> {
+        face(&self.0.main).underline_metrics().map(|m| m.position)
+    }
+
+    #[getter]
+    fn underline_thickness(&self) -> Option<i16> {
+        face(&self.0.main).underline_metrics().map(|m| m.thickness)
+    }
+
+    /// Whether any font of the fallback chain has a glyph for `ch`.
+    fn has_glyph(&self, ch: char) -> bool {
+        self.font_for(ch).is_some()
+    }
+
+    /// The characters of `text` no font of the fallback chain has glyphs
+    /// for, each once, in the order they first appear.
+    fn missing_chars(&self, text: &str) -> Vec<char> {
+        let mut missing = Vec::new();
+        for c in text.chars() {
+            if !c.is_control() && !missing.contains(&c) && self.font_for(c).is_none() {
+                missing.push(c);
+            }
+        }
+        missing
+    }
+
+    /// Each character of `text` with the index in the fallback chain of the
+    /// font it's drawn with, 0 being the main font, or None without one.
+    fn resolve(&self, text: &str) -> Vec<(char, Option<usize>)> {
+        text.chars().map(|c| (c, self.font_for(c))).collect()
+    }
+}
+
+impl Font {
+    /// The font with fallbacks found in the FontDB for the characters of
+    /// `text` it has no glyphs for, when auto fallbacks are turned on.
+    pub fn discover(&self, text: &str, draw_emojis: bool) -> Option<Font> {
+        discovery::extend(&self.0, text, draw_emojis)
+            .map(|font| Font(font, self.1.clone(), self.2.clone()))
+    }
+
+    /// Loads a fallback at the font's variations, taking the axes it has.
+    fn fallback(&self, font: FallbackArg) -> PyResult<rusttype::Font<'static>> {
+        varied(font.load()?, &self.2, false)
+    }
+
+    /// The index in the fallback chain of the first font with a glyph for
+    /// `c`, like [`crate::layout::resolve_glyph`] picks.
+    fn font_for(&self, c: char) -> Option<usize> {
+        std::iter::once(&self.0.main)
+            .chain(&self.0.fallbacks)
+            .position(|font| font.glyph(c).id().0 != 0)
+    }
+
+    fn build(
+        font: rusttype::Font<'static>,
+        fallbacks: Option<Vec<FallbackArg>>,
+        emoji_options: Option<EmojiOptions>,
+        features: Option<Features>,
+        variations: Option<Variations>,
+    ) -> PyResult<Self> {
+        let fallbacks = fallbacks
+            .unwrap_or_default()
+            .into_iter()
+            .map(FallbackArg::load)
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let font = SuperFont::with_emoji_options(
             font,
             fallbacks,
             emoji_options.unwrap_or_default().to_emoji_options(),
-        )))
+        );
+        let variations = variations.unwrap_or_default();
+        Ok(Font(
+            vary(font, &variations)?,
+            features.unwrap_or_default(),
+            variations,
+        ))
     }
+}
 
-    pub fn set_emoji_options(&mut self, emoji_options: EmojiOptions) {
-        self.0.emoji_options = emoji_options.to_emoji_options();
+/// Where `list.insert` puts an item at `index` of a list of `len` items.
+/// Negative indices count from the end, and indices out of range are
+/// clamped to the list.
+fn insert_index(index: isize, len: usize) -> usize {
+    let index = if index < 0 {
+        index.saturating_add_unsigned(len)
+    } else {
+        index
+    };
+    (index.max(0) as usize).min(len)
+}
+
+/// Where `list.pop` takes an item at `index` from a list of `len` items, if
+/// it's in range. Negative indices count from the end.
+fn pop_index(index: isize, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        index.checked_add_unsigned(len)?
+    } else {
+        index
+    };
+    usize::try_from(index).ok().filter(|&index| index < len)
+}
+
+/// The index of a face in its font file, with its family and style names.
+type FaceInfo = (u32, Option<String>, Option<String>);
+
+/// A font file, given by its path or its contents.
+#[derive(FromPyObject)]
+pub enum FontSource<'a> {
+    Path(String),
+    Bytes(&'a [u8]),
+}
+
+impl FontSource<'_> {
+    /// Loads the first face of the font.
+    fn load(self) -> Result<rusttype::Font<'static>, String> {
+        match self {
+            FontSource::Path(path) => load_font(&path),
+            FontSource::Bytes(data) => font_from_vec(data.to_vec(), 0),
+        }
+    }
+}
+
+/// A fallback font, given as a loaded font, whose parsed data is shared, or as
+/// a font file. Loaded fonts are added without their own fallbacks.
+#[derive(FromPyObject)]
+pub enum FallbackArg<'a> {
+    Font(PyRef<'a, Font>),
+    Source(FontSource<'a>),
+}
+
+impl FallbackArg<'_> {
+    fn load(self) -> PyResult<rusttype::Font<'static>> {
+        match self {
+            FallbackArg::Font(font) => Ok(font.0.main.clone()),
+            FallbackArg::Source(source) => source.load().map_err(load_error),
+        }
     }
 }
 
+/// Loads the font at `path`, or the face at `index` of a font collection.
+fn load_face(path: &str, index: u32) -> Result<rusttype::Font<'static>, String> {
+    match index {
+        0 => load_font(path),
+        index => font_from_vec(std::fs::read(path).map_err(|e| e.to_string())?, index),
+    }
+}
+
+/// Parses a font, or the face at `index` of a font collection.
+pub fn font_from_vec(data: Vec<u8>, index: u32) -> Result<rusttype::Font<'static>, String> {
+    // rusttype doesn't say why fonts fail to parse
+    owned_ttf_parser::Face::from_slice(&data, index).map_err(|e| e.to_string())?;
+    rusttype::Font::try_from_vec_and_index(data, index).ok_or_else(|| "Invalid font".to_string())
+}
+
+/// The first of the names with these ids a face has.
+fn face_name(face: &owned_ttf_parser::Face, ids: &[u16]) -> Option<String> {
+    ids.iter().find_map(|&id| {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == id)
+            .find_map(|name| name.to_string())
+    })
+}
+
+fn load_error(e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load font: {}", e))
+}
+
 #[pyclass]
 pub struct FontDB;
 
 #[allow(non_snake_case)]
 #[pymethods]
 impl FontDB {
+    /// Loads a font from its path, `index` picking the face of a font
+    /// collection.
     #[staticmethod]
-    pub fn LoadFromPath(name: &str, path: &str) -> PyResult<()> {
+    pub fn LoadFromPath(name: &str, path: &str, index: Option<u32>) -> PyResult<()> {
+        let index = index.unwrap_or(0);
+        if index != 0 {
+            // imagetext only loads the first face of a file
+            let font = load_face(path, index).map_err(load_error)?;
+            discovery::remove(name);
+            discovery::add_font(name, font.clone());
+            write_lock(&LOADED_FONTS).insert(name.to_string(), font);
+            return Ok(());
+        }
+
         imagetext::fontdb::FontDB::load_from_path(name, path).map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load font: {}", e))
-        })
+        })?;
+        // fonts loaded under the name before would be found first
+        write_lock(&LOADED_FONTS).remove(name);
+        discovery::remove(name);
+        discovery::add_path(Some(name), std::path::Path::new(path));
+        Ok(())
+    }
+
+    #[staticmethod]
+    pub fn LoadFromBytes(name: &str, data: &[u8], index: Option<u32>) -> PyResult<()> {
+        let font = font_from_vec(data.to_vec(), index.unwrap_or(0)).map_err(load_error)?;
+        discovery::remove(name);
+        discovery::add_font(name, font.clone());
+        write_lock(&LOADED_FONTS).insert(name.to_string(), font);
+        Ok(())
     }
 
     #[staticmethod]
     pub fn LoadFromDir(path: &str) {
-        imagetext::fontdb::FontDB::load_from_dir(path)
+        imagetext::fontdb::FontDB::load_from_dir(path);
+        discovery::add_path(None, std::path::Path::new(path));
     }
 
     #[staticmethod]
     pub fn LoadSystemFonts() {
-        imagetext::fontdb::FontDB::load_system_fonts()
+        imagetext::fontdb::FontDB::load_system_fonts();
+        discovery::add_system_fonts();
     }
 
     #[staticmethod]
-    pub fn Query(query: &str) -> PyResult<Font> {
-        let font = imagetext::fontdb::FontDB::query(query).ok_or_else(|| {
+    pub fn Query(query: &str, variations: Option<Variations>) -> PyResult<Font> {
+        let font = query_fonts(query, None).ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                 "No fonts found for query: {}",
                 query
             ))
         })?;
-        Ok(Font(font))
+        let variations = variations.unwrap_or_default();
+        Ok(Font(
+            vary(font, &variations)?,
+            Features::default(),
+            variations,
+        ))
     }
 
     #[staticmethod]
-    pub fn Get(name: &str) -> PyResult<Font> {
-        let font = imagetext::fontdb::FontDB::get(name).ok_or_else(|| {
+    pub fn Get(name: &str, variations: Option<Variations>) -> PyResult<Font> {
+        let font = get_font(name).ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                 "No fonts found for name: {}",
                 name
             ))
         })?;
-        Ok(Font(SuperFont::new(font, vec![])))
+        let font = SuperFont::new(font, vec![]);
+        let variations = variations.unwrap_or_default();
+        Ok(Font(
+            vary(font, &variations)?,
+            Features::default(),
+            variations,
+        ))
     }
 
     #[staticmethod]
-    pub fn QueryWithEmoji(query: &str, emoji_options: EmojiOptions) -> PyResult<Font> {
-        let font =
-            imagetext::fontdb::FontDB::query_with_emoji(query, emoji_options.to_emoji_options())
-                .ok_or_else(|| {
-                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                        "No fonts found for query: {}",
-                        query
-                    ))
-                })?;
-        Ok(Font(font))
+    pub fn QueryWithEmoji(
+        query: &str,
+        emoji_options: EmojiOptions,
+        variations: Option<Variations>,
+    ) -> PyResult<Font> {
+        let font = query_fonts(query, Some(emoji_options.to_emoji_options())).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "No fonts found for query: {}",
+                query
+            ))
+        })?;
+        let variations = variations.unwrap_or_default();
+        Ok(Font(
+            vary(font, &variations)?,
+            Features::default(),
+            variations,
+        ))
     }
 
+    /// Removes the fonts loaded under `name`, failing when there are none.
     #[staticmethod]
     pub fn Remove(name: &str) -> PyResult<()> {
-        imagetext::fontdb::FontDB::remove(name).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to remove font: {}", e))
-        })
+        discovery::remove(name);
+        let loaded = write_lock(&LOADED_FONTS).remove(name).is_some();
+        match imagetext::fontdb::FontDB::remove(name) {
+            Err(_) if loaded => Ok(()),
+            removed => removed.map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to remove font: {}",
+                    e
+                ))
+            }),
+        }
     }
 
     #[staticmethod]
     pub fn SetDefaultEmojiOptions(emoji_options: EmojiOptions) {
+        *write_lock(&DEFAULT_EMOJI_OPTIONS) = Some(emoji_options.to_emoji_options());
         imagetext::fontdb::FontDB::set_default_emoji_options(emoji_options.to_emoji_options())
     }
+
+    #[staticmethod]
+    pub fn SetAutoFallbacks(enabled: bool) {
+        discovery::set_enabled(enabled)
+    }
+}
+
+/// Fonts loaded into the FontDB from bytes or from faces of collections past
+/// the first, which imagetext doesn't load. Names are looked up here before
+/// imagetext's own fonts.
+static LOADED_FONTS: RwLock<BTreeMap<String, rusttype::Font<'static>>> =
+    RwLock::new(BTreeMap::new());
+
+/// The FontDB's default emoji options, for queries of fonts loaded from bytes.
+static DEFAULT_EMOJI_OPTIONS: RwLock<Option<imagetext::emoji::EmojiOptions>> = RwLock::new(None);
+
+fn write_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_lock<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Gets a font of the FontDB by name.
+pub fn get_font(name: &str) -> Option<rusttype::Font<'static>> {
+    let loaded = read_lock(&LOADED_FONTS).get(name).cloned();
+    loaded.or_else(|| imagetext::fontdb::FontDB::get(name))
+}
+
+/// Queries the FontDB for the fonts named in `query`, separated by spaces,
+/// the first being the main font and the rest its fallbacks.
+pub fn query_fonts(
+    query: &str,
+    emoji_options: Option<imagetext::emoji::EmojiOptions>,
+) -> Option<SuperFont<'static>> {
+    let uses_loaded = {
+        let loaded = read_lock(&LOADED_FONTS);
+        query
+            .split_whitespace()
+            .any(|name| loaded.contains_key(name))
+    };
+    if !uses_loaded {
+        return match emoji_options {
+            Some(emoji_options) => {
+                imagetext::fontdb::FontDB::query_with_emoji(query, emoji_options)
+            }
+            None => imagetext::fontdb::FontDB::query(query),
+        };
+    }
+
+    let mut fonts = query.split_whitespace().filter_map(get_font);
+    let main = fonts.next()?;
+    let emoji_options = emoji_options
+        .or_else(|| read_lock(&DEFAULT_EMOJI_OPTIONS).clone())
+        .unwrap_or_else(|| EmojiOptions::default().to_emoji_options());
+    Some(SuperFont::with_emoji_options(
+        main,
+        fonts.collect(),
+        emoji_options,
+    ))
+}
+
+/// The parsed OpenType face behind a font.
+pub fn face<'a>(font: &'a rusttype::Font<'static>) -> &'a owned_ttf_parser::Face<'a> {
+    match font {
+        rusttype::Font::Ref(face) => face,
+        rusttype::Font::Owned(face) => face.as_face_ref(),
+    }
+}
+
+/// Values for the variation axes of a variable font, given as a dict of axis
+/// tags to values, like `{"wght": 650, "wdth": 85}`.
+#[derive(Clone, Default)]
+pub struct Variations(pub Vec<(rustybuzz::Tag, f32)>);
+
+impl<'source> FromPyObject<'source> for Variations {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let variations: BTreeMap<String, f32> = ob.extract()?;
+        variations
+            .into_iter()
+            .map(|(tag, value)| Ok((parse_tag(&tag, "variation axis")?, value)))
+            .collect::<PyResult<_>>()
+            .map(Variations)
+    }
+}
+
+/// Draws a font and its fallbacks at an instance of their variation axes.
+/// The main font must have every axis, fallbacks take the ones they have.
+fn vary(font: SuperFont<'static>, variations: &Variations) -> PyResult<SuperFont<'static>> {
+    Ok(SuperFont {
+        main: varied(font.main, variations, true)?,
+        fallbacks: font
+            .fallbacks
+            .into_iter()
+            .map(|fallback| varied(fallback, variations, false))
+            .collect::<PyResult<_>>()?,
+        emoji_options: font.emoji_options,
+    })
+}
+
+/// `font` at an instance of its variation axes, failing on axes it doesn't
+/// have when `strict`. Fonts are kept as they are without variations.
+fn varied(
+    font: rusttype::Font<'static>,
+    variations: &Variations,
+    strict: bool,
+) -> PyResult<rusttype::Font<'static>> {
+    if variations.0.is_empty() {
+        return Ok(font);
+    }
+
+    with_variations(&font, variations, strict).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to set font variations: {}",
+            e
+        ))
+    })
+}
+
+/// A copy of `font` with its variation axes set, so its outlines and
+/// advances are those of that instance. Fails on axes the font doesn't have
+/// when `strict`.
+pub fn with_variations(
+    font: &rusttype::Font<'static>,
+    variations: &Variations,
+    strict: bool,
+) -> Result<rusttype::Font<'static>, String> {
+    fn set(face: &mut impl FaceMut, variations: &Variations, strict: bool) -> Result<(), String> {
+        for (axis, value) in &variations.0 {
+            if face.set_variation(*axis, *value).is_none() && strict {
+                return Err(format!("The font has no {} axis", axis));
+            }
+        }
+        Ok(())
+    }
+
+    match font {
+        rusttype::Font::Ref(face) => {
+            let mut face = owned_ttf_parser::Face::clone(face);
+            set(&mut face, variations, strict)?;
+            Ok(rusttype::Font::Ref(Arc::new(face)))
+        }
+        rusttype::Font::Owned(owned) => {
+            let data = owned.as_slice();
+            let index = face_index(data, owned.as_face_ref());
+            let mut face = owned_ttf_parser::OwnedFace::from_vec(data.to_vec(), index)
+                .map_err(|e| e.to_string())?;
+            set(&mut face, variations, strict)?;
+            Ok(rusttype::Font::Owned(Arc::new(face)))
+        }
+    }
+}
+
+/// The index of `face` in the font data it was parsed from, which holds
+/// several faces when it's a font collection.
+fn face_index(data: &[u8], face: &owned_ttf_parser::Face) -> u32 {
+    // faces of a collection each have their own head table
+    let head = |face: &owned_ttf_parser::Face| {
+        face.table_data(owned_ttf_parser::Tag::from_bytes(b"head"))
+            .map(|table| table.as_ptr() as usize - data.as_ptr() as usize)
+    };
+    let target = head(face);
+
+    (0..owned_ttf_parser::fonts_in_collection(data).unwrap_or(1))
+        .find(|&index| {
+            owned_ttf_parser::Face::from_slice(data, index)
+                .is_ok_and(|other| head(&other) == target)
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{font, superfont};
+
+    fn wght(value: f32) -> Variations {
+        Variations(vec![(rustybuzz::Tag::from_bytes(b"wght"), value)])
+    }
+
+    fn error(err: PyErr) -> String {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| err.value(py).to_string())
+    }
+
+    #[test]
+    fn variations_are_read_from_dicts() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dict = pyo3::types::PyDict::new(py);
+            dict.set_item("wght", 650).unwrap();
+            dict.set_item("wdth", 85.5).unwrap();
+            let variations: Variations = dict.extract().unwrap();
+            assert_eq!(
+                variations.0,
+                [
+                    (rustybuzz::Tag::from_bytes(b"wdth"), 85.5),
+                    (rustybuzz::Tag::from_bytes(b"wght"), 650.0),
+                ]
+            );
+
+            dict.set_item("weight", 1).unwrap();
+            assert_eq!(
+                error(dict.extract::<Variations>().err().unwrap()),
+                "Invalid OpenType variation axis tag: \"weight\""
+            );
+        });
+    }
+
+    #[test]
+    fn missing_axes_fail_only_when_strict() {
+        let font = font();
+        assert_eq!(
+            with_variations(&font, &wght(650.0), true).err().unwrap(),
+            "The font has no wght axis"
+        );
+
+        let varied = with_variations(&font, &wght(650.0), false).unwrap();
+        let advance = |font: &rusttype::Font| {
+            font.glyph('a')
+                .scaled(Scale::uniform(20.0))
+                .h_metrics()
+                .advance_width
+        };
+        assert_eq!(advance(&varied), advance(&font));
+    }
+
+    #[test]
+    fn the_main_font_must_have_every_axis() {
+        let unvaried = vary(superfont(), &Variations::default()).unwrap();
+        assert!(unvaried.fallbacks.is_empty());
+
+        assert_eq!(
+            error(vary(superfont(), &wght(650.0)).err().unwrap()),
+            "Failed to set font variations: The font has no wght axis"
+        );
+    }
+
+    #[test]
+    fn single_fonts_are_the_first_face() {
+        let data = std::fs::read(crate::testing::FONT_PATH).unwrap();
+        let face = owned_ttf_parser::Face::from_slice(&data, 0).unwrap();
+        assert_eq!(face_index(&data, &face), 0);
+    }
+
+    #[test]
+    fn faces_of_collections_load_by_index() {
+        let data = crate::testing::collection(2);
+        let path = std::env::temp_dir().join("imagetext_py_faces_of_collections.ttc");
+        std::fs::write(&path, &data).unwrap();
+        let path = path.to_str().unwrap();
+        let index = |font: &rusttype::Font<'static>| match font {
+            rusttype::Font::Owned(owned) => face_index(owned.as_slice(), owned.as_face_ref()),
+            rusttype::Font::Ref(_) => unreachable!(),
+        };
+
+        let font = Font::new(path, None, None, None, None, Some(1)).unwrap();
+        assert_eq!(index(&font.0.main), 1);
+        assert!(load_face(path, 2).is_err());
+
+        FontDB::LoadFromPath("collection face", path, Some(1)).unwrap();
+        let loaded = get_font("collection face").unwrap();
+        assert_eq!(index(&loaded), 1);
+        write_lock(&LOADED_FONTS).remove("collection face");
+        discovery::remove("collection face");
+    }
+
+    #[test]
+    fn metrics_come_from_the_main_font() {
+        let font = Font(superfont(), Features::default(), Variations::default());
+        assert_eq!(font.family().as_deref(), Some("Jellee"));
+        assert_eq!(font.units_per_em(), 1000);
+        assert!(font.ascent() > 0 && font.descent() < 0);
+        assert!(font
+            .cap_height()
+            .is_some_and(|cap| cap > font.x_height().unwrap_or(0)));
+        assert!(font
+            .underline_thickness()
+            .is_some_and(|thickness| thickness > 0));
+    }
+
+    #[test]
+    fn coverage_follows_the_fallback_chain() {
+        let single = Font(superfont(), Features::default(), Variations::default());
+        assert!(single.has_glyph('a'));
+        assert!(!single.has_glyph('漢'));
+        // each character once, in order, without control characters
+        assert_eq!(single.missing_chars("a漢b\nש漢"), ['漢', 'ש']);
+        assert_eq!(single.resolve("a漢"), [('a', Some(0)), ('漢', None)]);
+
+        let chained = Font(
+            SuperFont::new(font(), vec![font()]),
+            Features::default(),
+            Variations::default(),
+        );
+        assert_eq!(chained.resolve("ab"), [('a', Some(0)), ('b', Some(0))]);
+        assert!(chained.missing_chars("ab").is_empty());
+    }
+
+    #[test]
+    fn fallbacks_insert_like_lists() {
+        assert_eq!(insert_index(0, 3), 0);
+        assert_eq!(insert_index(2, 3), 2);
+        assert_eq!(insert_index(5, 3), 3);
+        assert_eq!(insert_index(-1, 3), 2);
+        assert_eq!(insert_index(-3, 3), 0);
+        assert_eq!(insert_index(-5, 3), 0);
+        assert_eq!(insert_index(isize::MIN, 0), 0);
+
+        let data = std::fs::read(crate::testing::FONT_PATH).unwrap();
+        let source = || FallbackArg::Source(FontSource::Bytes(&data));
+        let mut font = Font(superfont(), Features::default(), Variations::default());
+        font.insert_fallback(-1, source()).unwrap();
+        font.insert_fallback(-1, source()).unwrap();
+        font.insert_fallback(10, source()).unwrap();
+        assert_eq!(font.0.fallbacks.len(), 3);
+        font.remove_fallback(2).unwrap();
+        assert!(font.remove_fallback(2).is_err());
+    }
+
+    #[test]
+    fn fallbacks_pop_like_lists() {
+        assert_eq!(pop_index(0, 3), Some(0));
+        assert_eq!(pop_index(2, 3), Some(2));
+        assert_eq!(pop_index(3, 3), None);
+        assert_eq!(pop_index(-1, 3), Some(2));
+        assert_eq!(pop_index(-3, 3), Some(0));
+        assert_eq!(pop_index(-4, 3), None);
+        assert_eq!(pop_index(0, 0), None);
+        assert_eq!(pop_index(isize::MIN, 3), None);
+
+        let data = std::fs::read(crate::testing::FONT_PATH).unwrap();
+        let mut font = Font(
+            SuperFont::new(font(), vec![font(), font()]),
+            Features::default(),
+            Variations::default(),
+        );
+        font.remove_fallback(-1).unwrap();
+        assert_eq!(font.0.fallbacks.len(), 1);
+        let err = font.remove_fallback(-2).unwrap_err();
+        assert_eq!(error(err), "Fallback index out of range: -2");
+
+        // added fallbacks take the axes they have, like the font's own
+        let mut font = Font(superfont(), Features::default(), wght(650.0));
+        let source = || FallbackArg::Source(FontSource::Bytes(&data));
+        font.add_fallback(source()).unwrap();
+        font.insert_fallback(0, source()).unwrap();
+        assert_eq!(font.0.fallbacks.len(), 2);
+        let copy = font.with_fallbacks(vec![source()]).unwrap();
+        assert_eq!(copy.0.fallbacks.len(), 1);
+        assert_eq!(copy.2 .0, wght(650.0).0);
+    }
 }
@@ -0,0 +1,872 @@
+use imagetext::prelude::*;
+use rusttype::ScaledGlyph;
+
+use crate::bidi;
+use crate::objects::{Direction, TextAlign, WrapStyle};
+use crate::shaping::Shaper;
+
+/// imagetext replaces parsed emojis with this character before measuring.
+pub const EMOJI_PLACEHOLDER: char = '😀';
+
+pub enum GlyphKind {
+    Outline(ScaledGlyph<'static>),
+    Emoji(EmojiType),
+}
+
+pub struct Glyph {
+    pub kind: GlyphKind,
+    /// The pen position the glyph is drawn from.
+    pub x: f32,
+    /// How far the shaper moved the glyph from the pen position, y growing
+    /// downwards.
+    pub offset: (f32, f32),
+    pub advance: f32,
+    /// The bidi embedding level of the glyph's characters, odd levels run
+    /// right to left.
+    pub level: u8,
+    /// The index of the run the glyph was laid out from.
+    pub run: usize,
+    /// The index of the glyph's first character in the run's text.
+    pub index: usize,
+    pub scale: Scale,
+}
+
+impl Glyph {
+    /// Whether the glyph draws nothing, like whitespace.
+    pub fn is_blank(&self) -> bool {
+        match &self.kind {
+            GlyphKind::Outline(glyph) => glyph.exact_bounding_box().is_none(),
+            GlyphKind::Emoji(_) => false,
+        }
+    }
+}
+
+pub struct Line {
+    pub glyphs: Vec<Glyph>,
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    /// The font size of the largest run on the line.
+    pub size: f32,
+    /// Whether the line's paragraph runs right to left.
+    pub rtl: bool,
+}
+
+/// Laid out lines, positioned relative to the top left of the text block.
+pub struct Block {
+    pub lines: Vec<(Line, f32, f32)>,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Extra space added after every character, and after every whitespace
+/// character on top of that.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Spacing {
+    pub letter: f32,
+    pub word: f32,
+}
+
+impl Spacing {
+    pub fn new(letter: Option<f32>, word: Option<f32>) -> Self {
+        Spacing {
+            letter: letter.unwrap_or(0.0),
+            word: word.unwrap_or(0.0),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.letter == 0.0 && self.word == 0.0
+    }
+
+    /// The width spacing adds to a line of text, with emojis already parsed
+    /// out of it. Letter spacing isn't added after the last character.
+    pub fn extra_width(&self, text: &str) -> f32 {
+        let (count, spaces) = text
+            .chars()
+            .filter(|c| !c.is_control())
+            .fold((0usize, 0usize), |(count, spaces), c| {
+                (count + 1, spaces + c.is_whitespace() as usize)
+            });
+
+        self.letter * count.saturating_sub(1) as f32 + self.word * spaces as f32
+    }
+}
+
+/// The text imagetext parses back into `emoji`.
+pub fn emoji_text(emoji: &EmojiType) -> String {
+    match emoji {
+        EmojiType::Discord(id) => format!("<:ee:{}>", id),
+        EmojiType::Regular(e) => e.to_string(),
+    }
+}
+
+/// Finds the first font in the fallback chain that has a glyph for `c`,
+/// returning its index in the chain along with the glyph.
+pub fn resolve_glyph(font: &SuperFont<'static>, c: char) -> (usize, rusttype::Glyph<'static>) {
+    std::iter::once(&font.main)
+        .chain(font.fallbacks.iter())
+        .map(|f| f.glyph(c))
+        .enumerate()
+        .find(|(_, g)| g.id().0 != 0)
+        .unwrap_or_else(|| (0, font.main.glyph(c)))
+}
+
+/// Lays out `text` starting at `x`, returning its glyphs, whether each of them
+/// is whitespace, and where the text ends.
+///
+/// Glyphs are laid out in logical order, `levels` holds the bidi embedding
+/// level of each character of the text, missing levels being 0.
+pub fn layout_run(
+    text: &str,
+    font: &SuperFont<'static>,
+    scale: Scale,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    levels: &[u8],
+    run: usize,
+    x: f32,
+) -> (Vec<(Glyph, bool)>, f32) {
+    let level_at = |position: usize| levels.get(position).copied().unwrap_or(0);
+
+    let (text, emojis) = if draw_emojis {
+        imagetext::emoji::parse::parse_out_emojis(
+            text,
+            font.emoji_options.parse_shortcodes,
+            font.emoji_options.parse_discord_emojis,
+        )
+    } else {
+        (text.to_string(), Vec::new())
+    };
+
+    if let Some(shaper) = shaper {
+        return shape_run(
+            &text,
+            emojis,
+            font,
+            scale,
+            draw_emojis,
+            spacing,
+            shaper,
+            &level_at,
+            run,
+            x,
+        );
+    }
+
+    let mut emojis = emojis.into_iter();
+    let emoji_size = scale.y * font.emoji_options.scale;
+
+    let mut glyphs = Vec::new();
+    let mut x = x;
+    let mut last: Option<(usize, rusttype::GlyphId, u8)> = None;
+    let mut position = 0;
+
+    for c in text.chars() {
+        let at = position;
+        position += 1;
+        if c.is_control() {
+            continue;
+        }
+
+        if draw_emojis && c == EMOJI_PLACEHOLDER {
+            if let Some(emoji) = emojis.next() {
+                // the placeholder stands for all of the emoji's characters
+                position = at + emoji_text(&emoji).chars().count();
+                let glyph = Glyph {
+                    kind: GlyphKind::Emoji(emoji),
+                    x,
+                    offset: (0.0, 0.0),
+                    advance: emoji_size,
+                    level: level_at(at),
+                    run,
+                    index: at,
+                    scale,
+                };
+                glyphs.push((glyph, false));
+                x += emoji_size + spacing.letter;
+                last = None;
+                continue;
+            }
+        }
+
+        let level = level_at(at);
+        let (index, glyph) = resolve_glyph(font, bidi::mirror(c, level));
+        let glyph = glyph.scaled(scale);
+
+        // kerning pairs are in visual order, right to left text isn't kerned
+        if let Some((last_index, last_id, last_level)) = last {
+            if last_index == index && last_level == level && level % 2 == 0 {
+                x += glyph.font().pair_kerning(scale, last_id, glyph.id());
+            }
+        }
+        last = Some((index, glyph.id(), level));
+
+        let advance = glyph.h_metrics().advance_width;
+        let glyph = Glyph {
+            kind: GlyphKind::Outline(glyph),
+            x,
+            offset: (0.0, 0.0),
+            advance,
+            level,
+            run,
+            index: at,
+            scale,
+        };
+        glyphs.push((glyph, c.is_whitespace()));
+        x += advance + spacing.letter;
+        if c.is_whitespace() {
+            x += spacing.word;
+        }
+    }
+
+    (glyphs, x)
+}
+
+/// A piece of a run shaped on its own.
+enum Piece {
+    Emoji(EmojiType, usize),
+    /// Text drawn with the font at this index of the fallback chain, at a
+    /// bidi embedding level.
+    Text(usize, std::ops::Range<usize>, u8),
+}
+
+/// [`layout_run`] with a shaper, given text with its emojis parsed out.
+///
+/// The text is split where its font in the fallback chain or its bidi level
+/// changes, staying with the current font for as long as it has glyphs so
+/// that marks are shaped with their base characters. Right to left pieces
+/// are shaped right to left, then put back in logical order.
+fn shape_run(
+    text: &str,
+    emojis: Vec<EmojiType>,
+    font: &SuperFont<'static>,
+    scale: Scale,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: &Shaper,
+    level_at: &dyn Fn(usize) -> u8,
+    run: usize,
+    x: f32,
+) -> (Vec<(Glyph, bool)>, f32) {
+    let fonts: Vec<_> = std::iter::once(&font.main)
+        .chain(font.fallbacks.iter())
+        .collect();
+    let emoji_size = scale.y * font.emoji_options.scale;
+
+    let mut emojis = emojis.into_iter();
+    let mut pieces = Vec::new();
+    // the position in the run's text of the character at each byte
+    let mut positions = vec![0; text.len()];
+    let mut position = 0;
+
+    for (byte, c) in text.char_indices() {
+        positions[byte] = position;
+
+        if draw_emojis && c == EMOJI_PLACEHOLDER {
+            if let Some(emoji) = emojis.next() {
+                position += emoji_text(&emoji).chars().count();
+                pieces.push(Piece::Emoji(emoji, byte));
+                continue;
+            }
+        }
+
+        let level = level_at(position);
+        position += 1;
+        if c.is_control() {
+            continue;
+        }
+
+        let end = byte + c.len_utf8();
+        match pieces.last_mut() {
+            Some(Piece::Text(index, range, piece_level))
+                if range.end == byte
+                    && *piece_level == level
+                    && (c.is_whitespace() || fonts[*index].glyph(c).id().0 != 0) =>
+            {
+                range.end = end
+            }
+            _ => pieces.push(Piece::Text(resolve_glyph(font, c).0, byte..end, level)),
+        }
+    }
+
+    let mut glyphs = Vec::new();
+    let mut x = x;
+
+    for piece in pieces {
+        match piece {
+            Piece::Emoji(emoji, byte) => {
+                let glyph = Glyph {
+                    kind: GlyphKind::Emoji(emoji),
+                    x,
+                    offset: (0.0, 0.0),
+                    advance: emoji_size,
+                    level: level_at(positions[byte]),
+                    run,
+                    index: positions[byte],
+                    scale,
+                };
+                glyphs.push((glyph, false));
+                x += emoji_size + spacing.letter;
+            }
+            Piece::Text(index, range, level) => {
+                let rtl = level % 2 == 1;
+                let mut shaped = shaper.shape(fonts[index], scale, &text[range.clone()], rtl);
+                if rtl {
+                    shaped.reverse();
+                }
+
+                for (i, shaped_glyph) in shaped.iter().enumerate() {
+                    let byte = range.start + shaped_glyph.cluster;
+                    let space = text[byte..].starts_with(char::is_whitespace);
+                    let glyph = Glyph {
+                        kind: GlyphKind::Outline(fonts[index].glyph(shaped_glyph.id).scaled(scale)),
+                        x,
+                        offset: shaped_glyph.offset,
+                        advance: shaped_glyph.advance,
+                        level,
+                        run,
+                        index: positions[byte],
+                        scale,
+                    };
+                    glyphs.push((glyph, space));
+                    x += shaped_glyph.advance;
+
+                    // spacing goes between clusters, not inside ligatures,
+                    // after the glyph drawn last of each cluster
+                    let neighbour = if rtl {
+                        i.checked_sub(1).and_then(|i| shaped.get(i))
+                    } else {
+                        shaped.get(i + 1)
+                    };
+                    let cluster_ends = !matches!(
+                        neighbour,
+                        Some(next) if next.cluster == shaped_glyph.cluster
+                    );
+                    if cluster_ends {
+                        x += spacing.letter;
+                        if space {
+                            x += spacing.word;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (glyphs, x)
+}
+
+/// Lays out `text` as a paragraph of its own, in logical order.
+pub fn layout_line(
+    text: &str,
+    font: &SuperFont<'static>,
+    scale: Scale,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> Line {
+    let (levels, rtl) = bidi::levels(text, direction);
+    let (glyphs, end) = layout_run(
+        text,
+        font,
+        scale,
+        draw_emojis,
+        spacing,
+        shaper,
+        &levels,
+        0,
+        0.0,
+    );
+    let v_metrics = font.main.v_metrics(scale);
+
+    Line {
+        // letter spacing only goes between characters
+        width: if glyphs.is_empty() {
+            0.0
+        } else {
+            end - spacing.letter
+        },
+        glyphs: glyphs.into_iter().map(|(glyph, _)| glyph).collect(),
+        ascent: v_metrics.ascent,
+        descent: v_metrics.descent,
+        size: scale.y,
+        rtl,
+    }
+}
+
+/// Lays out each of `lines` on its own, indexing glyphs into the lines
+/// joined by newlines.
+pub fn layout_lines(
+    lines: &[String],
+    font: &SuperFont<'static>,
+    scale: Scale,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> Vec<Line> {
+    let mut start = 0;
+    lines
+        .iter()
+        .map(|text| {
+            let mut line = layout_line(text, font, scale, draw_emojis, spacing, shaper, direction);
+            line.glyphs.iter_mut().for_each(|g| g.index += start);
+            start += text.chars().count() + 1;
+            line
+        })
+        .collect()
+}
+
+/// The vertical metrics of the run a glyph came from.
+#[derive(Clone, Copy)]
+pub struct RunMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub size: f32,
+}
+
+impl RunMetrics {
+    pub fn new(font: &SuperFont<'static>, scale: Scale) -> Self {
+        let v_metrics = font.main.v_metrics(scale);
+        RunMetrics {
+            ascent: v_metrics.ascent,
+            descent: v_metrics.descent,
+            size: scale.y,
+        }
+    }
+}
+
+/// Builds a line out of glyphs laid out on a longer line, moving them so the
+/// line starts at 0. `metrics` are used when the line has no glyphs.
+fn make_line(glyphs: Vec<(Glyph, bool)>, metrics: &[RunMetrics], fallback: RunMetrics) -> Line {
+    let origin = glyphs.first().map(|(g, _)| g.x).unwrap_or(0.0);
+    let width = glyphs
+        .iter()
+        .filter(|(_, space)| !space)
+        .map(|(g, _)| g.x + g.advance - origin)
+        .fold(0.0, f32::max);
+
+    let mut line_metrics: Option<RunMetrics> = None;
+    let glyphs = glyphs
+        .into_iter()
+        .map(|(mut glyph, _)| {
+            let m = metrics[glyph.run];
+            line_metrics = Some(match line_metrics {
+                Some(l) => RunMetrics {
+                    ascent: l.ascent.max(m.ascent),
+                    descent: l.descent.min(m.descent),
+                    size: l.size.max(m.size),
+                },
+                None => m,
+            });
+            glyph.x -= origin;
+            glyph
+        })
+        .collect();
+    let line_metrics = line_metrics.unwrap_or(fallback);
+
+    Line {
+        glyphs,
+        width,
+        ascent: line_metrics.ascent,
+        descent: line_metrics.descent,
+        size: line_metrics.size,
+        rtl: false,
+    }
+}
+
+/// Breaks a laid out line into lines no wider than `width`, breaking after
+/// whitespace when wrapping by word. Words wider than `width` are broken
+/// between characters.
+pub fn wrap_glyphs(
+    glyphs: Vec<(Glyph, bool)>,
+    width: Option<f32>,
+    style: WrapStyle,
+    metrics: &[RunMetrics],
+    fallback: RunMetrics,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut current: Vec<(Glyph, bool)> = Vec::new();
+    // where the current line can be broken, after a run of whitespace
+    let mut break_at: Option<usize> = None;
+
+    for (glyph, space) in glyphs {
+        if let (Some(width), Some((first, _))) = (width, current.first()) {
+            if !space && glyph.x + glyph.advance - first.x > width {
+                let split = match style {
+                    WrapStyle::Word => break_at
+                        .filter(|&at| current[..at].iter().any(|(_, space)| !space))
+                        .unwrap_or(current.len()),
+                    WrapStyle::Character => current.len(),
+                };
+                let mut rest = current.split_off(split);
+                while current.last().is_some_and(|(_, space)| *space) {
+                    current.pop();
+                }
+                rest.retain(|(_, space)| !space);
+                lines.push(make_line(current, metrics, fallback));
+
+                current = rest;
+                break_at = None;
+            }
+        }
+
+        // whitespace that wrapped onto a new line is dropped
+        if space && current.is_empty() && !lines.is_empty() {
+            continue;
+        }
+
+        current.push((glyph, space));
+        if space {
+            break_at = Some(current.len());
+        }
+    }
+
+    lines.push(make_line(current, metrics, fallback));
+    lines
+}
+
+/// Wraps `text` to `width` as it's laid out, returning the text of each
+/// line with emojis written back in and whitespace between lines dropped.
+pub fn wrap_text(
+    text: &str,
+    width: f32,
+    font: &SuperFont<'static>,
+    scale: Scale,
+    draw_emojis: bool,
+    style: WrapStyle,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> Vec<String> {
+    let metrics = RunMetrics::new(font, scale);
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        // glyphs index the characters of the text with emojis written out
+        let chars: Vec<char> = if draw_emojis {
+            let (parsed, emojis) = imagetext::emoji::parse::parse_out_emojis(
+                paragraph,
+                font.emoji_options.parse_shortcodes,
+                font.emoji_options.parse_discord_emojis,
+            );
+            let mut emojis = emojis.iter().map(emoji_text);
+            let written: String = parsed
+                .chars()
+                .map(|c| match c {
+                    EMOJI_PLACEHOLDER => emojis.next().unwrap_or_else(|| c.to_string()),
+                    c => c.to_string(),
+                })
+                .collect();
+            written.chars().collect()
+        } else {
+            paragraph.chars().collect()
+        };
+
+        let (levels, _) = bidi::levels(paragraph, direction);
+        let (glyphs, _) = layout_run(
+            paragraph,
+            font,
+            scale,
+            draw_emojis,
+            spacing,
+            shaper,
+            &levels,
+            0,
+            0.0,
+        );
+        let wrapped = wrap_glyphs(glyphs, Some(width), style, &[metrics], metrics);
+
+        let starts: Vec<usize> = wrapped
+            .iter()
+            .enumerate()
+            .map(|(i, line)| match line.glyphs.first() {
+                Some(glyph) if i > 0 => glyph.index,
+                _ => 0,
+            })
+            .collect();
+        for (i, &start) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).copied().unwrap_or(chars.len());
+            let line: String = chars[start..end].iter().collect();
+            lines.push(if i + 1 < starts.len() {
+                line.trim_end().to_string()
+            } else {
+                line
+            });
+        }
+    }
+
+    lines
+}
+
+impl Line {
+    /// Widens the gaps between words so the line spans `width`, lines
+    /// without gaps are left as they are.
+    fn justify(&mut self, width: f32) {
+        let extra = width - self.width;
+        let blank: Vec<bool> = self.glyphs.iter().map(Glyph::is_blank).collect();
+        // a gap starts where whitespace follows ink, with more ink after it
+        let last_ink = blank.iter().rposition(|blank| !blank).unwrap_or(0);
+        let gaps: Vec<usize> = (1..last_ink)
+            .filter(|&i| blank[i] && !blank[i - 1])
+            .collect();
+        if gaps.is_empty() || extra <= 0.0 {
+            return;
+        }
+
+        let share = extra / gaps.len() as f32;
+        let mut gaps = gaps.into_iter().peekable();
+        let mut offset = 0.0;
+        for (i, glyph) in self.glyphs.iter_mut().enumerate() {
+            if gaps.next_if_eq(&i).is_some() {
+                offset += share;
+            }
+            glyph.x += offset;
+        }
+        self.width = width;
+    }
+
+    /// Puts glyphs laid out in logical order into visual order, moving them
+    /// along the line. Whitespace ending the line takes the direction of the
+    /// paragraph.
+    fn reorder(&mut self) {
+        let base = self.rtl as u8;
+        for glyph in self.glyphs.iter_mut().rev() {
+            if !glyph.is_blank() {
+                break;
+            }
+            glyph.level = base;
+        }
+        if self.glyphs.iter().all(|g| g.level == 0) {
+            return;
+        }
+
+        // how far each glyph moves the pen, with kerning and spacing
+        let widths: Vec<f32> = (0..self.glyphs.len())
+            .map(|i| match self.glyphs.get(i + 1) {
+                Some(next) => next.x - self.glyphs[i].x,
+                None => self.glyphs[i].advance,
+            })
+            .collect();
+        let levels: Vec<u8> = self.glyphs.iter().map(|g| g.level).collect();
+
+        let mut x = self.glyphs.first().map_or(0.0, |g| g.x);
+        let mut glyphs: Vec<Option<Glyph>> = std::mem::take(&mut self.glyphs)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for i in bidi::visual_order(&levels) {
+            if let Some(mut glyph) = glyphs[i].take() {
+                glyph.x = x;
+                x += widths[i];
+                self.glyphs.push(glyph);
+            }
+        }
+    }
+}
+
+impl Block {
+    /// Stacks `lines` `line_spacing` times their font size apart, aligning
+    /// them within `width` when given, otherwise within the widest line.
+    /// Justified blocks leave their last line unjustified, unless justifying
+    /// all lines.
+    ///
+    /// Lines are given in logical order and drawn in visual order, starting
+    /// on the right when their paragraph runs right to left.
+    pub fn new(lines: Vec<Line>, line_spacing: f32, align: &TextAlign, width: Option<f32>) -> Self {
+        let width = width.unwrap_or_else(|| lines.iter().map(|l| l.width).fold(0.0, f32::max));
+
+        let count = lines.len();
+        let mut top = 0.0;
+        let mut height = 0.0;
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut line)| {
+                line.reorder();
+                let justify = match align {
+                    TextAlign::Justify => i + 1 < count,
+                    TextAlign::JustifyAll => true,
+                    _ => false,
+                };
+                if justify {
+                    line.justify(width);
+                }
+
+                let x = match align {
+                    TextAlign::Left => 0.0,
+                    TextAlign::Center => (width - line.width) / 2.0,
+                    TextAlign::Right => width - line.width,
+                    TextAlign::Start | TextAlign::Justify | TextAlign::JustifyAll if !line.rtl => {
+                        0.0
+                    }
+                    TextAlign::End if line.rtl => 0.0,
+                    _ => width - line.width,
+                };
+                let line_top = top;
+                height = top + (line.ascent - line.descent);
+                top += line.size * line_spacing;
+                (line, x, line_top)
+            })
+            .collect();
+
+        Block {
+            lines,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::superfont;
+
+    fn line(text: &str) -> Line {
+        layout_line(
+            text,
+            &superfont(),
+            scale(20.0),
+            false,
+            Spacing::default(),
+            None,
+            Direction::Ltr,
+        )
+    }
+
+    #[test]
+    fn justify_spreads_the_gaps_between_words() {
+        let mut justified = line(" ab  cd e ");
+        let plain = line(" ab  cd e ");
+        justified.justify(plain.width + 30.0);
+
+        assert_eq!(justified.width, plain.width + 30.0);
+        let moved: Vec<_> = justified
+            .glyphs
+            .iter()
+            .zip(&plain.glyphs)
+            .map(|(a, b)| (a.x - b.x).round())
+            .collect();
+        // leading and trailing whitespace isn't a gap, a run of it is one
+        assert_eq!(
+            moved,
+            [0.0, 0.0, 0.0, 15.0, 15.0, 15.0, 15.0, 30.0, 30.0, 30.0]
+        );
+    }
+
+    #[test]
+    fn justify_leaves_single_words_alone() {
+        for text in ["word", "  word  ", ""] {
+            let mut justified = line(text);
+            let plain = line(text);
+            justified.justify(plain.width + 30.0);
+
+            assert_eq!(justified.width, plain.width);
+            assert!(justified
+                .glyphs
+                .iter()
+                .zip(&plain.glyphs)
+                .all(|(a, b)| a.x == b.x));
+        }
+    }
+
+    #[test]
+    fn spacing_widens_laid_out_lines() {
+        let font = superfont();
+        let spaced = |letter, word| {
+            layout_line(
+                "ab cd",
+                &font,
+                scale(20.0),
+                false,
+                Spacing::new(letter, word),
+                None,
+                Direction::Ltr,
+            )
+        };
+        let plain = spaced(None, None);
+        let spacing = Spacing::new(Some(3.0), Some(7.0));
+
+        let line = spaced(Some(3.0), Some(7.0));
+        let grown = line.width - plain.width;
+        assert!((grown - spacing.extra_width("ab cd")).abs() < 1e-3);
+        assert!((line.glyphs[3].x - plain.glyphs[3].x - (3.0 * 3.0 + 7.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn extra_width_of_spacing() {
+        let spacing = Spacing::new(Some(2.0), Some(5.0));
+        assert_eq!(spacing.extra_width("ab c"), 3.0 * 2.0 + 5.0);
+        assert_eq!(spacing.extra_width("a\n"), 0.0);
+        assert_eq!(spacing.extra_width(""), 0.0);
+        assert!(Spacing::new(None, None).is_none());
+    }
+
+    #[test]
+    fn wrapping_text_keeps_its_words() {
+        let spacing = Spacing::new(Some(2.0), Some(4.0));
+        let font = superfont();
+        let width = line("aaa bbb").width;
+        for shaper in [None, Some(Shaper::default())] {
+            for direction in [Direction::Auto, Direction::Rtl] {
+                let lines = wrap_text(
+                    "aaa bbb ccc\n\nddd",
+                    width,
+                    &font,
+                    scale(20.0),
+                    false,
+                    WrapStyle::Word,
+                    spacing,
+                    shaper.as_ref(),
+                    direction,
+                );
+                assert_eq!(lines, ["aaa", "bbb", "ccc", "", "ddd"]);
+            }
+        }
+
+        let lines = wrap_text(
+            "aaaa",
+            line("aa").width + 1.0,
+            &font,
+            scale(20.0),
+            false,
+            WrapStyle::Character,
+            Spacing::default(),
+            None,
+            Direction::Auto,
+        );
+        assert_eq!(lines, ["aa", "aa"]);
+    }
+
+    #[test]
+    fn brackets_mirror_in_right_to_left_runs() {
+        let font = superfont();
+        let id = |c: char| font.main.glyph(c).id();
+        assert_ne!(id('('), id(')'));
+        for shaper in [None, Some(Shaper::default())] {
+            let line = layout_line(
+                "(a)",
+                &font,
+                scale(20.0),
+                false,
+                Spacing::default(),
+                shaper.as_ref(),
+                Direction::Rtl,
+            );
+            let ids: Vec<_> = line
+                .glyphs
+                .iter()
+                .map(|g| match &g.kind {
+                    GlyphKind::Outline(glyph) => glyph.id(),
+                    GlyphKind::Emoji(_) => unreachable!(),
+                })
+                .collect();
+            // the brackets are at level 1, the letter inside them at 2
+            assert_eq!(ids, [id(')'), id('a'), id('(')]);
+        }
+    }
+}
@@ -1,31 +1,71 @@
+// the pyo3 0.20 macros expand to impls inside of consts
+#![allow(non_local_definitions)]
+// python functions take their keyword arguments as parameters
+#![allow(clippy::too_many_arguments)]
+
+pub mod bidi;
+pub mod blend;
 pub mod canvas;
+pub mod decoration;
+pub mod discovery;
 pub mod drawing;
+pub mod effects;
 pub mod font;
+pub mod layout;
+pub mod markup;
 pub mod objects;
 pub mod paint;
+pub mod render;
+pub mod rich;
+pub mod shapes;
+pub mod shaping;
+pub mod stroke;
 pub mod utils;
 
+#[cfg(test)]
+mod testing;
+
 use pyo3::prelude::*;
 
 /// A Python module implemented in Rust.
 #[pymodule]
-fn imagetext_py(_py: Python, m: &PyModule) -> PyResult<()> {
+fn imagetext_py(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<canvas::Canvas>()?;
     m.add_class::<font::Font>()?;
     m.add_class::<font::FontDB>()?;
     m.add_class::<paint::Paint>()?;
+    m.add_class::<shapes::Path>()?;
+    m.add_class::<effects::Shadow>()?;
+    m.add_class::<decoration::Decoration>()?;
+    m.add_class::<stroke::Stroke>()?;
+    m.add_class::<rich::Span>()?;
+    m.add_class::<markup::Markup>()?;
+    m.add("MarkupError", py.get_type::<markup::MarkupError>())?;
     m.add_class::<objects::TextAlign>()?;
     m.add_class::<objects::EmojiSource>()?;
     m.add_class::<objects::WrapStyle>()?;
+    m.add_class::<objects::BlendMode>()?;
+    m.add_class::<objects::LineJoin>()?;
+    m.add_class::<objects::LineCap>()?;
+    m.add_class::<objects::StrokePlacement>()?;
+    m.add_class::<objects::Overflow>()?;
+    m.add_class::<objects::DecorationLine>()?;
+    m.add_class::<objects::BackgroundMode>()?;
+    m.add_class::<objects::Direction>()?;
 
     m.add_function(wrap_pyfunction!(drawing::draw_text, m)?)?;
     m.add_function(wrap_pyfunction!(drawing::draw_text_anchored, m)?)?;
     m.add_function(wrap_pyfunction!(drawing::draw_text_multiline, m)?)?;
     m.add_function(wrap_pyfunction!(drawing::draw_text_wrapped, m)?)?;
+    m.add_function(wrap_pyfunction!(drawing::draw_text_fitted, m)?)?;
+    m.add_function(wrap_pyfunction!(drawing::draw_rich_text, m)?)?;
 
     m.add_function(wrap_pyfunction!(utils::text_size, m)?)?;
     m.add_function(wrap_pyfunction!(utils::text_size_multiline, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::text_lines_extents, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::rich_text_size, m)?)?;
     m.add_function(wrap_pyfunction!(utils::text_wrap, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::fit_text, m)?)?;
 
     m.add_function(wrap_pyfunction!(utils::prebuild_static_vars, m)?)?;
 
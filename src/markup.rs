@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+
+use imagetext::prelude::*;
+use pyo3::prelude::*;
+
+use crate::font::{query_fonts, Font};
+use crate::rich::Span;
+use crate::shaping::Features;
+use crate::stroke::Stroke;
+
+pyo3::create_exception!(imagetext_py, MarkupError, pyo3::exceptions::PyValueError);
+
+/// Parses BBCode-like markup into rich text spans.
+///
+/// `[b]` and `[i]` switch to the fonts queried from the FontDB with the
+/// matching names, `[font=NAME]` queries a font directly.
+#[derive(Clone)]
+#[pyclass]
+pub struct Markup {
+    pub bold: Option<String>,
+    pub italic: Option<String>,
+    pub bold_italic: Option<String>,
+}
+
+#[pymethods]
+impl Markup {
+    #[new]
+    fn new(bold: Option<String>, italic: Option<String>, bold_italic: Option<String>) -> Self {
+        Markup {
+            bold,
+            italic,
+            bold_italic,
+        }
+    }
+
+    #[pyo3(name = "parse")]
+    fn py_parse(
+        &self,
+        py: Python,
+        text: &str,
+        font: &Font,
+        size: f32,
+        fill: &crate::paint::Paint,
+        stroke: Option<crate::stroke::StrokeArg>,
+        stroke_color: Option<&crate::paint::Paint>,
+    ) -> PyResult<Vec<Span>> {
+        let stroke = stroke.map(crate::stroke::StrokeArg::into_stroke);
+        py.allow_threads(|| {
+            self.parse(
+                text,
+                &font.0,
+                size,
+                &fill.0,
+                stroke.as_ref(),
+                stroke_color.map(|c| &c.0),
+            )
+        })
+    }
+}
+
+#[derive(Clone)]
+struct State {
+    font: Option<String>,
+    bold: bool,
+    italic: bool,
+    size: f32,
+    fill: Paint<'static>,
+    stroke: Option<Stroke>,
+    stroke_color: Option<Paint<'static>>,
+}
+
+fn markup_error(message: String) -> PyErr {
+    PyErr::new::<MarkupError, _>(message)
+}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa` colors.
+fn parse_color(value: &str) -> Option<Paint<'static>> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8 * 17))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(
+        digits[0],
+        digits[1],
+        digits[2],
+        digits.get(3).copied().unwrap_or(255),
+    );
+    Some(paint)
+}
+
+impl Markup {
+    pub fn parse(
+        &self,
+        text: &str,
+        font: &SuperFont<'static>,
+        size: f32,
+        fill: &Paint<'static>,
+        stroke: Option<&Stroke>,
+        stroke_color: Option<&Paint<'static>>,
+    ) -> PyResult<Vec<Span>> {
+        let mut fonts: HashMap<String, SuperFont<'static>> = HashMap::new();
+        let mut spans = Vec::new();
+        let mut stack: Vec<(String, usize, State)> = Vec::new();
+        let mut state = State {
+            font: None,
+            bold: false,
+            italic: false,
+            size,
+            fill: fill.clone(),
+            stroke: stroke.cloned(),
+            stroke_color: stroke_color.cloned(),
+        };
+        let mut buffer = String::new();
+
+        let mut flush = |buffer: &mut String, state: &State| -> PyResult<()> {
+            if buffer.is_empty() {
+                return Ok(());
+            }
+
+            let query =
+                match (state.bold, state.italic) {
+                    (false, false) => state.font.as_ref(),
+                    (true, false) => Some(self.bold.as_ref().ok_or_else(|| {
+                        markup_error("No bold font was given for [b]".to_string())
+                    })?),
+                    (false, true) => Some(self.italic.as_ref().ok_or_else(|| {
+                        markup_error("No italic font was given for [i]".to_string())
+                    })?),
+                    (true, true) => Some(self.bold_italic.as_ref().ok_or_else(|| {
+                        markup_error("No bold italic font was given for [b][i]".to_string())
+                    })?),
+                };
+
+            let font = match query {
+                Some(query) => match fonts.get(query) {
+                    Some(font) => font.clone(),
+                    None => {
+                        let found = query_fonts(query, None).ok_or_else(|| {
+                            markup_error(format!("No fonts found for query: {}", query))
+                        })?;
+                        fonts.insert(query.clone(), found.clone());
+                        found
+                    }
+                },
+                None => font.clone(),
+            };
+
+            spans.push(Span {
+                text: std::mem::take(buffer),
+                font,
+                size: state.size,
+                fill: state.fill.clone(),
+                stroke: state.stroke.clone(),
+                stroke_color: state.stroke_color.clone(),
+                // the base font's features come with the shaper
+                features: Features::default(),
+            });
+            Ok(())
+        };
+
+        // positions in errors count characters, like Python string indices
+        let at = |pos: usize| text[..pos].chars().count();
+
+        let mut chars = text.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            if c != '[' {
+                buffer.push(c);
+                continue;
+            }
+
+            // "[[" is a literal bracket
+            if chars.next_if(|(_, c)| *c == '[').is_some() {
+                buffer.push('[');
+                continue;
+            }
+
+            let end = text[pos..]
+                .find(']')
+                .map(|end| pos + end)
+                .ok_or_else(|| markup_error(format!("Unclosed tag at position {}", at(pos))))?;
+            let tag = &text[pos + 1..end];
+            while chars.next_if(|(i, _)| *i <= end).is_some() {}
+
+            flush(&mut buffer, &state)?;
+
+            if let Some(name) = tag.strip_prefix('/') {
+                match stack.pop() {
+                    Some((open, _, previous)) if open == name => state = previous,
+                    Some((open, _, _)) => {
+                        return Err(markup_error(format!(
+                            "Expected [/{}] but found [/{}] at position {}",
+                            open,
+                            name,
+                            at(pos)
+                        )))
+                    }
+                    None => {
+                        return Err(markup_error(format!(
+                            "Unexpected [/{}] at position {}",
+                            name,
+                            at(pos)
+                        )))
+                    }
+                }
+                continue;
+            }
+
+            let (name, value) = match tag.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (tag, None),
+            };
+            let invalid = || {
+                markup_error(format!(
+                    "Invalid value for [{}] at position {}: {}",
+                    name,
+                    at(pos),
+                    value.unwrap_or("")
+                ))
+            };
+
+            stack.push((name.to_string(), pos, state.clone()));
+            match (name, value) {
+                ("b", None) => state.bold = true,
+                ("i", None) => state.italic = true,
+                ("font", Some(value)) => state.font = Some(value.to_string()),
+                ("size", Some(value)) => {
+                    state.size = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|size| *size > 0.0)
+                        .ok_or_else(invalid)?
+                }
+                ("color", Some(value)) => state.fill = parse_color(value).ok_or_else(invalid)?,
+                ("stroke", Some(value)) => {
+                    let width = value.parse::<f32>().map_err(|_| invalid())?;
+                    state.stroke = match state.stroke.take() {
+                        Some(mut stroke) => {
+                            stroke.stroke.width = width;
+                            Some(stroke)
+                        }
+                        None => Some(Stroke::from_width(width)),
+                    };
+                }
+                ("stroke_color", Some(value)) => {
+                    state.stroke_color = Some(parse_color(value).ok_or_else(invalid)?)
+                }
+                ("b" | "i", Some(_))
+                | ("font" | "size" | "color" | "stroke" | "stroke_color", None) => {
+                    return Err(invalid())
+                }
+                _ => {
+                    return Err(markup_error(format!(
+                        "Unknown tag [{}] at position {}",
+                        name,
+                        at(pos)
+                    )))
+                }
+            }
+        }
+
+        flush(&mut buffer, &state)?;
+
+        match stack.pop() {
+            Some((name, pos, _)) => Err(markup_error(format!(
+                "Tag [{}] at position {} is never closed",
+                name,
+                at(pos)
+            ))),
+            None => Ok(spans),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::superfont;
+
+    fn rgba(paint: &Paint) -> [u8; 4] {
+        let tiny_skia::Shader::SolidColor(color) = paint.shader else {
+            unreachable!()
+        };
+        let color = color.to_color_u8();
+        [color.red(), color.green(), color.blue(), color.alpha()]
+    }
+
+    fn parse(text: &str) -> PyResult<Vec<Span>> {
+        let markup = Markup::new(None, None, None);
+        markup.parse(text, &superfont(), 10.0, &Paint::default(), None, None)
+    }
+
+    fn error(text: &str) -> String {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let err = parse(text).err().unwrap();
+            assert!(err.is_instance_of::<MarkupError>(py));
+            err.value(py).to_string()
+        })
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(rgba(&parse_color("#abc").unwrap()), [170, 187, 204, 255]);
+        assert_eq!(rgba(&parse_color("#abc8").unwrap()), [170, 187, 204, 136]);
+        assert_eq!(rgba(&parse_color("#102030").unwrap()), [16, 32, 48, 255]);
+        assert_eq!(rgba(&parse_color("#10203040").unwrap()), [16, 32, 48, 64]);
+        for invalid in ["abc", "#12345", "#ggg", "#", "#ab€"] {
+            assert!(parse_color(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn tags_nest() {
+        let spans = parse("a[color=#f00]b[size=20]c[/size][/color]d").unwrap();
+        let runs: Vec<_> = spans
+            .iter()
+            .map(|s| (s.text.as_str(), s.size, rgba(&s.fill)))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                ("a", 10.0, [0, 0, 0, 255]),
+                ("b", 10.0, [255, 0, 0, 255]),
+                ("c", 20.0, [255, 0, 0, 255]),
+                ("d", 10.0, [0, 0, 0, 255]),
+            ]
+        );
+    }
+
+    #[test]
+    fn doubled_brackets_are_literal() {
+        let spans = parse("[[b] and [[").unwrap();
+        let text: Vec<_> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(text, ["[b] and ["]);
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn errors_point_at_the_tag() {
+        assert_eq!(error("é[size=2"), "Unclosed tag at position 1");
+        assert_eq!(error("[x]a[/x]"), "Unknown tag [x] at position 0");
+        assert_eq!(error("a[/b]"), "Unexpected [/b] at position 1");
+        assert_eq!(
+            error("[size=2]a[/color]"),
+            "Expected [/size] but found [/color] at position 9"
+        );
+        assert_eq!(
+            error("ab[size=2]c"),
+            "Tag [size] at position 2 is never closed"
+        );
+    }
+
+    #[test]
+    fn errors_for_invalid_values() {
+        assert_eq!(
+            error("a[size=0]b[/size]"),
+            "Invalid value for [size] at position 1: 0"
+        );
+        assert_eq!(
+            error("[color=red]a[/color]"),
+            "Invalid value for [color] at position 0: red"
+        );
+        assert_eq!(
+            error("[stroke]a[/stroke]"),
+            "Invalid value for [stroke] at position 0: "
+        );
+        assert_eq!(
+            error("[b=1]a[/b]"),
+            "Invalid value for [b] at position 0: 1"
+        );
+        assert_eq!(error("[b]a[/b]"), "No bold font was given for [b]");
+        assert_eq!(
+            error("[b][i]a[/i][/b]"),
+            "No bold italic font was given for [b][i]"
+        );
+    }
+}
@@ -3,21 +3,49 @@ use pyo3::prelude::*;
 #[derive(FromPyObject)]
 pub struct Color(pub [u8; 4]);
 
+/// Padding given either for all sides or as (horizontal, vertical).
+#[derive(FromPyObject)]
+pub enum Padding {
+    Uniform(f32),
+    Axes(f32, f32),
+}
+
+impl Padding {
+    pub fn to_axes(&self) -> (f32, f32) {
+        match self {
+            Padding::Uniform(padding) => (*padding, *padding),
+            Padding::Axes(x, y) => (*x, *y),
+        }
+    }
+}
+
 #[pyclass]
 pub enum TextAlign {
     Left,
     Center,
     Right,
+    Justify,
+    JustifyAll,
+    Start,
+    End,
 }
 
 impl TextAlign {
+    /// imagetext can't justify, justified text is drawn left aligned by it.
+    /// It only draws left to right text, where lines start on the left.
     pub fn to_align(&self) -> imagetext::outliner::TextAlign {
         match self {
-            TextAlign::Left => imagetext::outliner::TextAlign::Left,
+            TextAlign::Left | TextAlign::Justify | TextAlign::JustifyAll | TextAlign::Start => {
+                imagetext::outliner::TextAlign::Left
+            }
             TextAlign::Center => imagetext::outliner::TextAlign::Center,
-            TextAlign::Right => imagetext::outliner::TextAlign::Right,
+            TextAlign::Right | TextAlign::End => imagetext::outliner::TextAlign::Right,
         }
     }
+
+    pub fn is_justified(&self) -> bool {
+        matches!(self, TextAlign::Justify | TextAlign::JustifyAll)
+    }
 }
 
 #[derive(Clone)]
@@ -98,8 +126,8 @@ impl EmojiSource {
     }
 
     #[staticmethod]
-    pub fn Dir(path: String) {
-        imagetext::emoji::source::EmojiSource::Dir(path);
+    pub fn Dir(path: String) -> Self {
+        Self(imagetext::emoji::source::EmojiSource::Dir(path))
     }
 }
 
@@ -154,3 +182,95 @@ impl WrapStyle {
         }
     }
 }
+
+#[derive(Clone, Copy)]
+#[pyclass]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+#[derive(Clone, Copy)]
+#[pyclass]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl LineJoin {
+    pub fn to_line_join(&self) -> tiny_skia::LineJoin {
+        match self {
+            LineJoin::Miter => tiny_skia::LineJoin::Miter,
+            LineJoin::Round => tiny_skia::LineJoin::Round,
+            LineJoin::Bevel => tiny_skia::LineJoin::Bevel,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[pyclass]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+impl LineCap {
+    pub fn to_line_cap(&self) -> tiny_skia::LineCap {
+        match self {
+            LineCap::Butt => tiny_skia::LineCap::Butt,
+            LineCap::Round => tiny_skia::LineCap::Round,
+            LineCap::Square => tiny_skia::LineCap::Square,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum StrokePlacement {
+    Outside,
+    Center,
+    Inside,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum Overflow {
+    Clip,
+    Ellipsis,
+    Fade,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum BackgroundMode {
+    Line,
+    Block,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum DecorationLine {
+    Underline,
+    Strikethrough,
+    Overline,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[pyclass]
+pub enum Direction {
+    Auto,
+    Ltr,
+    Rtl,
+}
@@ -3,6 +3,7 @@ use crate::objects::Color;
 use imagetext::prelude::*;
 use pyo3::prelude::*;
 
+#[derive(Clone)]
 #[pyclass]
 pub struct Paint(pub imagetext::prelude::Paint<'static>);
 
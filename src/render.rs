@@ -0,0 +1,957 @@
+use imagetext::prelude::*;
+use rusttype::OutlineBuilder;
+use std::ops::Range;
+use tiny_skia::{
+    GradientStop, LinearGradient, Mask, MaskType, PathBuilder, Pixmap, PixmapMut, PixmapPaint,
+    Rect, SpreadMode, Transform,
+};
+
+use crate::canvas::with_layer;
+use crate::decoration::Decoration;
+use crate::effects::{draw_shadow, Shadow};
+use crate::layout::{emoji_text, Block, GlyphKind};
+use crate::objects::{BackgroundMode, DecorationLine, StrokePlacement};
+use crate::shapes;
+use crate::stroke::{self, Stroke};
+
+/// A 2x3 affine matrix (a, b, c, d, e, f), mapping (x, y) to
+/// (a * x + c * y + e, b * x + d * y + f).
+pub type Matrix = (f32, f32, f32, f32, f32, f32);
+
+/// Combines the optional rotation (in degrees) and matrix of a draw call,
+/// `None` when neither is given so the plain imagetext path can be used.
+pub fn user_transform(rotation: Option<f32>, transform: Option<Matrix>) -> Option<Transform> {
+    if rotation.is_none() && transform.is_none() {
+        return None;
+    }
+
+    let matrix = transform
+        .map(|(a, b, c, d, e, f)| Transform::from_row(a, b, c, d, e, f))
+        .unwrap_or_default();
+
+    Some(matrix.pre_concat(Transform::from_rotate(rotation.unwrap_or(0.0))))
+}
+
+struct PathOutliner<'a> {
+    builder: &'a mut PathBuilder,
+    x: f32,
+    y: f32,
+}
+
+impl OutlineBuilder for PathOutliner<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.builder.move_to(x + self.x, y + self.y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.builder.line_to(x + self.x, y + self.y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.builder
+            .quad_to(x1 + self.x, y1 + self.y, x + self.x, y + self.y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.builder.cubic_to(
+            x1 + self.x,
+            y1 + self.y,
+            x2 + self.x,
+            y2 + self.y,
+            x + self.x,
+            y + self.y,
+        );
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+/// The glyph outlines of each run on some lines of a block, in block space.
+pub fn block_paths(
+    block: &Block,
+    runs: usize,
+    lines: Range<usize>,
+) -> Vec<Option<tiny_skia::Path>> {
+    let mut builders = vec![PathBuilder::new(); runs];
+
+    for (line, x, y) in &block.lines[lines] {
+        for glyph in &line.glyphs {
+            if let GlyphKind::Outline(outline) = &glyph.kind {
+                outline.build_outline(&mut PathOutliner {
+                    builder: &mut builders[glyph.run],
+                    x: x + glyph.x + glyph.offset.0,
+                    y: y + line.ascent + glyph.offset.1,
+                });
+            }
+        }
+    }
+
+    builders.into_iter().map(PathBuilder::finish).collect()
+}
+
+/// Renders a single emoji with imagetext at the size it's laid out at,
+/// cropped to what was drawn. `None` when nothing was.
+fn emoji_pixmap(
+    emoji: &EmojiType,
+    font: &SuperFont<'static>,
+    scale: Scale,
+) -> Result<Option<Pixmap>, String> {
+    let size = scale.y * font.emoji_options.scale;
+    let pad = (size / 2.0).ceil();
+    let side = (size + pad * 2.0).ceil() as u32;
+
+    // the shift is applied when the emoji is placed
+    let font = SuperFont::with_emoji_options(
+        font.main.clone(),
+        font.fallbacks.clone(),
+        imagetext::emoji::EmojiOptions {
+            shift: (0, 0),
+            ..font.emoji_options.clone()
+        },
+    );
+    let mut scratch = image::RgbaImage::new(side, side);
+    let mut transparent = Paint::default();
+    transparent.set_color_rgba8(0, 0, 0, 0);
+
+    imagetext::drawing::text::draw_text_mut_with_emojis(
+        &mut scratch,
+        &transparent,
+        Outline::None,
+        pad,
+        pad,
+        scale,
+        &font,
+        DefaultEmojiResolver::<true>,
+        &emoji_text(emoji),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let drawn = scratch
+        .enumerate_pixels()
+        .filter(|(_, _, px)| px.0[3] > 0)
+        .map(|(x, y, _)| (x, y, x, y))
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)));
+    let Some((left, top, right, bottom)) = drawn else {
+        return Ok(None);
+    };
+    let Some(mut pixmap) = Pixmap::new(right - left + 1, bottom - top + 1) else {
+        return Ok(None);
+    };
+
+    let width = pixmap.width();
+    for (i, px) in pixmap.pixels_mut().iter_mut().enumerate() {
+        let (x, y) = (left + i as u32 % width, top + i as u32 / width);
+        let [r, g, b, a] = scratch.get_pixel(x, y).0;
+        *px = tiny_skia::ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+    Ok(Some(pixmap))
+}
+
+/// Maps an emoji pixmap of `(width, height)` into the square box of side
+/// `size` at (x, y), scaled to fit and centered.
+fn place_emoji((width, height): (u32, u32), x: f32, y: f32, size: f32) -> Transform {
+    let fit = size / width.max(height) as f32;
+    Transform::from_translate(
+        x + (size - width as f32 * fit) / 2.0,
+        y + (size - height as f32 * fit) / 2.0,
+    )
+    .pre_scale(fit, fit)
+}
+
+/// How the glyphs of one run of a block are drawn.
+pub struct RunStyle<'a> {
+    pub font: &'a SuperFont<'static>,
+    pub fill: &'a Paint<'static>,
+    pub outline: Option<(&'a Stroke, &'a Paint<'static>)>,
+}
+
+/// A box drawn behind each line of a block, or behind all of it.
+pub struct Background<'a> {
+    pub paint: &'a Paint<'static>,
+    /// Horizontal and vertical padding around the text.
+    pub padding: (f32, f32),
+    pub corner_radius: f32,
+    pub mode: BackgroundMode,
+}
+
+/// A range of characters of one run painted behind.
+pub struct Highlight<'a> {
+    pub run: usize,
+    pub range: Range<usize>,
+    pub paint: &'a Paint<'static>,
+}
+
+/// Splits character ranges of the text of all runs joined together into
+/// ranges of each run, given the length of each run in characters.
+pub fn run_highlights<'a>(
+    highlights: &'a [(usize, usize, crate::paint::Paint)],
+    lengths: impl IntoIterator<Item = usize>,
+) -> Vec<Highlight<'a>> {
+    let mut start = 0;
+    let mut runs = Vec::new();
+
+    for (run, length) in lengths.into_iter().enumerate() {
+        let end = start + length;
+        for (from, to, paint) in highlights {
+            let range = *from.max(&start)..*to.min(&end);
+            if !range.is_empty() {
+                runs.push(Highlight {
+                    run,
+                    range: range.start - start..range.end - start,
+                    paint: &paint.0,
+                });
+            }
+        }
+        start = end;
+    }
+
+    runs
+}
+
+/// How a block is drawn by [`draw_block`].
+pub struct TextStyle<'a> {
+    /// Indexed by the run of each glyph.
+    pub runs: Vec<RunStyle<'a>>,
+    /// Strokes drawn beneath the fill, widest first.
+    pub outlines: &'a [(Stroke, Paint<'static>)],
+    pub transform: Option<Transform>,
+    pub shadow: Option<&'a Shadow>,
+    pub glow: Option<&'a Shadow>,
+    /// Fades out the end of the last line over this many pixels.
+    pub fade: Option<f32>,
+    pub decorations: &'a [Decoration],
+    pub background: Option<Background<'a>>,
+    pub highlights: Vec<Highlight<'a>>,
+}
+
+impl TextStyle<'_> {
+    /// Whether imagetext can draw this style on its own.
+    pub fn is_plain(&self) -> bool {
+        self.transform.is_none()
+            && self.shadow.is_none()
+            && self.glow.is_none()
+            && self.outlines.is_empty()
+            && self.fade.is_none()
+            && self.decorations.is_empty()
+            && self.background.is_none()
+            && self.highlights.is_empty()
+            && self
+                .runs
+                .iter()
+                .filter_map(|run| run.outline)
+                .all(|(s, _)| s.placement == StrokePlacement::Center)
+    }
+}
+
+fn union(a: Rect, b: Rect) -> Rect {
+    Rect::from_ltrb(
+        a.left().min(b.left()),
+        a.top().min(b.top()),
+        a.right().max(b.right()),
+        a.bottom().max(b.bottom()),
+    )
+    .unwrap_or(a)
+}
+
+/// The area of the canvas covered by the glyphs and emojis of a block.
+fn coverage_bounds(
+    path_bounds: Option<Rect>,
+    emoji_bounds: impl IntoIterator<Item = Rect>,
+    stroke_width: f32,
+    transform: Transform,
+) -> Option<Rect> {
+    let bounds = path_bounds.into_iter().chain(emoji_bounds).reduce(union)?;
+
+    PathBuilder::from_rect(bounds.outset(stroke_width, stroke_width)?)
+        .transform(transform)
+        .map(|p| p.bounds())
+}
+
+/// The area of the canvas a block draws on, its glyphs, backdrop and
+/// shadows, with a pixel to spare for antialiasing.
+fn drawn_bounds(
+    block: &Block,
+    style: &TextStyle,
+    coverage: Option<Rect>,
+    transform: Transform,
+) -> Option<Rect> {
+    let mut areas: Vec<Rect> = coverage.into_iter().collect();
+
+    if let Some(coverage) = coverage {
+        let shadows = style.shadow.map(|shadow| (shadow, shadow.offset));
+        let glows = style.glow.map(|glow| (glow, (0.0, 0.0)));
+        for (shadow, offset) in shadows.into_iter().chain(glows) {
+            areas.extend(shadow.area(coverage, offset));
+        }
+    }
+
+    if style.background.is_some() || !style.highlights.is_empty() {
+        // highlights can reach past the end of a line over its whitespace
+        let boxes = block.lines.iter().filter_map(|(line, x, y)| {
+            let right = line
+                .glyphs
+                .iter()
+                .map(|g| g.x + g.advance)
+                .fold(line.width, f32::max);
+            Rect::from_xywh(*x, *y, right, line.ascent - line.descent)
+        });
+        let (px, py) = style.background.as_ref().map_or((0.0, 0.0), |b| b.padding);
+        let backdrop = boxes
+            .reduce(union)
+            .and_then(|r| r.outset(px.max(0.0), py.max(0.0)))
+            .and_then(|r| r.transform(transform));
+        areas.extend(backdrop);
+    }
+
+    areas.into_iter().reduce(union)?.outset(1.0, 1.0)
+}
+
+/// A mask fading out the end of a line of a block over `length` pixels.
+fn fade_mask(
+    (width, height): (u32, u32),
+    block: &Block,
+    line: usize,
+    length: f32,
+    transform: Transform,
+) -> Option<Mask> {
+    let (line, lx, _) = &block.lines[line];
+    let end = lx + line.width;
+    let start = end - length.min(line.width);
+
+    let shader = LinearGradient::new(
+        tiny_skia::Point::from_xy(start, 0.0),
+        tiny_skia::Point::from_xy(end, 0.0),
+        vec![
+            GradientStop::new(0.0, tiny_skia::Color::BLACK),
+            GradientStop::new(1.0, tiny_skia::Color::TRANSPARENT),
+        ],
+        SpreadMode::Pad,
+        transform,
+    )?;
+
+    let mut pixmap = Pixmap::new(width, height)?;
+    pixmap.fill_rect(
+        Rect::from_xywh(0.0, 0.0, width as f32, height as f32)?,
+        &Paint {
+            shader,
+            ..Default::default()
+        },
+        Transform::identity(),
+        None,
+    );
+    Some(Mask::from_pixmap(pixmap.as_ref(), MaskType::Alpha))
+}
+
+/// Draws the glyphs of `paths`, indexed by run. When drawing coverage every
+/// run is drawn opaque, along with its strokes.
+fn draw_paths(
+    pixmap: &mut PixmapMut,
+    style: &TextStyle,
+    paths: &[Option<tiny_skia::Path>],
+    transform: Transform,
+    clip: Option<&Mask>,
+    coverage: bool,
+) {
+    let opaque = Paint::default();
+
+    for path in paths.iter().flatten() {
+        for (stroke, fill) in style.outlines {
+            let fill = if coverage { &opaque } else { fill };
+            stroke::stroke_path(pixmap, path, fill, stroke, transform, clip);
+        }
+    }
+
+    for (run, path) in style.runs.iter().zip(paths) {
+        if let Some(path) = path {
+            let fill = if coverage { &opaque } else { run.fill };
+            pixmap.fill_path(path, fill, tiny_skia::FillRule::Winding, transform, clip);
+
+            if let Some((stroke, fill)) = run.outline {
+                let fill = if coverage { &opaque } else { fill };
+                stroke::stroke_path(pixmap, path, fill, stroke, transform, clip);
+            }
+        }
+    }
+}
+
+/// A decoration with its lines for each run, and the thickest of them.
+type DecorationPaths<'a> = (&'a Decoration, Vec<Option<tiny_skia::Path>>, f32);
+
+/// A mask of everything but the glyphs of `paths` and a gap of `gap` pixels
+/// around them, which decorations skipping ink are drawn through. The mask
+/// is the size of the layer the block is drawn on.
+fn ink_gaps(
+    (width, height): (u32, u32),
+    paths: &[Option<tiny_skia::Path>],
+    gap: f32,
+    transform: Transform,
+    clip: Option<&Mask>,
+) -> Option<Mask> {
+    let mut mask = Mask::new(width, height)?;
+    let outline = tiny_skia::Stroke {
+        width: gap * 2.0,
+        line_join: tiny_skia::LineJoin::Round,
+        ..Default::default()
+    };
+    let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
+
+    for path in paths.iter().flatten() {
+        mask.fill_path(path, tiny_skia::FillRule::Winding, true, transform);
+        if let Some(outline) = path.stroke(&outline, res_scale) {
+            mask.fill_path(&outline, tiny_skia::FillRule::Winding, true, transform);
+        }
+    }
+
+    mask.invert();
+    if let Some(clip) = clip {
+        stroke::intersect_mask(&mut mask, clip);
+    }
+    Some(mask)
+}
+
+/// Draws the decorations drawn over the text, or the ones drawn beneath it,
+/// of the glyphs of `paths`.
+fn draw_decorations(
+    pixmap: &mut PixmapMut,
+    style: &TextStyle,
+    paths: &[Option<tiny_skia::Path>],
+    decorations: &[DecorationPaths],
+    over: bool,
+    transform: Transform,
+    clip: Option<&Mask>,
+    coverage: bool,
+) {
+    let opaque = Paint::default();
+
+    for (decoration, lines, thickest) in decorations {
+        // strikethroughs cross the text, the other lines go beneath it
+        if (decoration.line == DecorationLine::Strikethrough) != over {
+            continue;
+        }
+
+        let gaps = decoration
+            .skip_ink
+            .then(|| {
+                let size = (pixmap.width(), pixmap.height());
+                ink_gaps(size, paths, *thickest, transform, clip)
+            })
+            .flatten();
+        let clip = gaps.as_ref().or(clip);
+
+        for (run, line) in style.runs.iter().zip(lines) {
+            if let Some(line) = line {
+                let fill = match &decoration.paint {
+                    _ if coverage => &opaque,
+                    Some(paint) => paint,
+                    None => run.fill,
+                };
+                pixmap.fill_path(line, fill, tiny_skia::FillRule::Winding, transform, clip);
+            }
+        }
+    }
+}
+
+/// Draws the background and highlights of a block, which are left out of
+/// its shadows.
+fn draw_backdrop(pixmap: &mut PixmapMut, block: &Block, style: &TextStyle, transform: Transform) {
+    let boxes = block
+        .lines
+        .iter()
+        .filter(|(line, _, _)| !line.glyphs.is_empty())
+        .filter_map(|(line, x, y)| Rect::from_xywh(*x, *y, line.width, line.ascent - line.descent));
+
+    if let Some(background) = &style.background {
+        let (px, py) = background.padding;
+        let boxes: Vec<_> = match background.mode {
+            BackgroundMode::Line => boxes.collect(),
+            BackgroundMode::Block => boxes.reduce(union).into_iter().collect(),
+        };
+
+        for rect in boxes {
+            let path = shapes::rounded_rect(
+                rect.x() - px,
+                rect.y() - py,
+                rect.width() + px * 2.0,
+                rect.height() + py * 2.0,
+                background.corner_radius,
+            );
+            if let Some(path) = path {
+                pixmap.fill_path(
+                    &path,
+                    background.paint,
+                    tiny_skia::FillRule::Winding,
+                    transform,
+                    None,
+                );
+            }
+        }
+    }
+
+    for highlight in &style.highlights {
+        let mut builder = PathBuilder::new();
+        for (line, x, y) in &block.lines {
+            let marked = |g: &crate::layout::Glyph| {
+                g.run == highlight.run && highlight.range.contains(&g.index)
+            };
+            for segment in line.glyphs.chunk_by(|a, b| marked(a) == marked(b)) {
+                let (first, last) = (&segment[0], &segment[segment.len() - 1]);
+                if !marked(first) {
+                    continue;
+                }
+                if let Some(rect) = Rect::from_xywh(
+                    x + first.x,
+                    *y,
+                    last.x + last.advance - first.x,
+                    line.ascent - line.descent,
+                ) {
+                    builder.push_rect(rect);
+                }
+            }
+        }
+
+        if let Some(path) = builder.finish() {
+            pixmap.fill_path(
+                &path,
+                highlight.paint,
+                tiny_skia::FillRule::Winding,
+                transform,
+                None,
+            );
+        }
+    }
+}
+
+/// Draws a laid out block with its anchor point (ax, ay) placed at (x, y),
+/// the style's transform is applied around the anchor point.
+pub fn draw_block<C>(
+    im: &mut image::ImageBuffer<image::Rgba<u8>, C>,
+    block: &Block,
+    x: f32,
+    y: f32,
+    ax: f32,
+    ay: f32,
+    style: &TextStyle,
+) -> Result<(), String>
+where
+    C: std::ops::DerefMut<Target = [u8]>,
+{
+    let transform = Transform::from_translate(x, y)
+        .pre_concat(style.transform.unwrap_or_default())
+        .pre_translate(-ax * block.width, -ay * block.height);
+
+    // each emoji is rendered once per run, and placed by the layout
+    let mut rendered: Vec<(usize, String, Option<Pixmap>)> = Vec::new();
+    let mut emojis = Vec::new();
+    for (i, (line, lx, ly)) in block.lines.iter().enumerate() {
+        for glyph in &line.glyphs {
+            if let GlyphKind::Emoji(emoji) = &glyph.kind {
+                let font = style.runs[glyph.run].font;
+                let text = emoji_text(emoji);
+                let at = match rendered
+                    .iter()
+                    .position(|(run, t, _)| *run == glyph.run && *t == text)
+                {
+                    Some(at) => at,
+                    None => {
+                        let pixmap = emoji_pixmap(emoji, font, glyph.scale)?;
+                        rendered.push((glyph.run, text, pixmap));
+                        rendered.len() - 1
+                    }
+                };
+                let Some(pixmap) = &rendered[at].2 else {
+                    continue;
+                };
+
+                // emojis of smaller runs sit on the line's baseline
+                let (shift_x, shift_y) = font.emoji_options.shift;
+                let top = ly + line.ascent - font.main.v_metrics(glyph.scale).ascent;
+                let place = place_emoji(
+                    (pixmap.width(), pixmap.height()),
+                    lx + glyph.x + shift_x as f32,
+                    top + shift_y as f32,
+                    glyph.scale.y * font.emoji_options.scale,
+                );
+                emojis.push((at, place, i));
+            }
+        }
+    }
+
+    // the faded line is drawn on its own, through a mask
+    let faded = style
+        .fade
+        .filter(|_| !block.lines.is_empty())
+        .map(|length| (block.lines.len() - 1, length));
+    let runs = style.runs.len();
+    let body = 0..faded.map_or(block.lines.len(), |(i, _)| i);
+    let paths = block_paths(block, runs, body.clone());
+    let faded_paths = faded.map(|(i, length)| (block_paths(block, runs, i..i + 1), i, length));
+
+    let fonts: Vec<_> = style.runs.iter().map(|run| run.font).collect();
+    let decorate = |lines: Range<usize>| -> Vec<DecorationPaths> {
+        style
+            .decorations
+            .iter()
+            .map(|decoration| {
+                let (paths, thickest) = decoration.paths(block, &fonts, lines.clone());
+                (decoration, paths, thickest)
+            })
+            .collect()
+    };
+    let decorations = decorate(body.clone());
+    let faded_decorations = faded.map(|(i, _)| decorate(i..i + 1)).unwrap_or_default();
+
+    let emoji_paint = PixmapPaint {
+        quality: tiny_skia::FilterQuality::Bilinear,
+        ..Default::default()
+    };
+    let draw_emojis =
+        |pixmap: &mut PixmapMut, transform: Transform, lines: Range<usize>, clip: Option<&Mask>| {
+            for (at, place, i) in &emojis {
+                if let (true, Some(emoji)) = (lines.contains(i), &rendered[*at].2) {
+                    pixmap.draw_pixmap(
+                        0,
+                        0,
+                        emoji.as_ref(),
+                        &emoji_paint,
+                        transform.pre_concat(*place),
+                        clip,
+                    );
+                }
+            }
+        };
+
+    let draw = |pixmap: &mut PixmapMut, transform: Transform, coverage: bool| {
+        let draw_lines = |pixmap: &mut PixmapMut,
+                          paths: &[Option<tiny_skia::Path>],
+                          decorations: &[DecorationPaths],
+                          lines: Range<usize>,
+                          clip: Option<&Mask>| {
+            draw_decorations(
+                pixmap,
+                style,
+                paths,
+                decorations,
+                false,
+                transform,
+                clip,
+                coverage,
+            );
+            draw_paths(pixmap, style, paths, transform, clip, coverage);
+            draw_emojis(pixmap, transform, lines, clip);
+            draw_decorations(
+                pixmap,
+                style,
+                paths,
+                decorations,
+                true,
+                transform,
+                clip,
+                coverage,
+            );
+        };
+
+        draw_lines(pixmap, &paths, &decorations, body.clone(), None);
+
+        if let Some((faded_paths, i, length)) = &faded_paths {
+            let size = (pixmap.width(), pixmap.height());
+            if let Some(mask) = fade_mask(size, block, *i, *length, transform) {
+                draw_lines(
+                    pixmap,
+                    faded_paths,
+                    &faded_decorations,
+                    *i..*i + 1,
+                    Some(&mask),
+                );
+            }
+        }
+    };
+
+    let stroke_width = style
+        .runs
+        .iter()
+        .filter_map(|run| run.outline.map(|(s, _)| s))
+        .chain(style.outlines.iter().map(|(s, _)| s))
+        // miters reach furthest from the outline
+        .map(|s| s.stroke.width * s.stroke.miter_limit.max(1.0))
+        .fold(0.0, f32::max);
+    let decoration_paths = decorations
+        .iter()
+        .chain(&faded_decorations)
+        .flat_map(|(_, paths, _)| paths);
+    let path_bounds = paths
+        .iter()
+        .chain(faded_paths.iter().flat_map(|(paths, _, _)| paths))
+        .chain(decoration_paths)
+        .flatten()
+        .map(|p| p.bounds())
+        .reduce(union);
+    let emoji_bounds = emojis.iter().filter_map(|(at, place, _)| {
+        let emoji = rendered[*at].2.as_ref()?;
+        Rect::from_xywh(0.0, 0.0, emoji.width() as f32, emoji.height() as f32)?.transform(*place)
+    });
+    let bounds = coverage_bounds(path_bounds, emoji_bounds, stroke_width, transform);
+
+    // the layer only covers what the block draws on, masks made to its size
+    // are too
+    let Some(area) = drawn_bounds(block, style, bounds, transform) else {
+        return Ok(());
+    };
+    with_layer(im, area, |pixmap, to_layer| {
+        let transform = to_layer.pre_concat(transform);
+        draw_backdrop(pixmap, block, style, transform);
+
+        // shadows take their shape from everything the text covers, drawn opaque
+        let draw_coverage = |pixmap: &mut PixmapMut, to_shadow: Transform| {
+            draw(pixmap, to_shadow.pre_concat(transform), true);
+        };
+        if let Some(bounds) = bounds.and_then(|b| b.transform(to_layer)) {
+            if let Some(shadow) = style.shadow {
+                draw_shadow(pixmap, shadow, shadow.offset, bounds, draw_coverage);
+            }
+            if let Some(glow) = style.glow {
+                draw_shadow(pixmap, glow, (0.0, 0.0), bounds, draw_coverage);
+            }
+        }
+
+        draw(pixmap, transform, false);
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maps(transform: Transform, (x, y): (f32, f32)) -> (f32, f32) {
+        let mut point = [tiny_skia::Point::from_xy(x, y)];
+        transform.map_points(&mut point);
+        (
+            (point[0].x * 1e3).round() / 1e3,
+            (point[0].y * 1e3).round() / 1e3,
+        )
+    }
+
+    #[test]
+    fn user_transform_rotates_then_applies_the_matrix() {
+        assert!(user_transform(None, None).is_none());
+
+        let rotate = user_transform(Some(90.0), None).unwrap();
+        assert_eq!(maps(rotate, (1.0, 0.0)), (0.0, 1.0));
+
+        let matrix = user_transform(None, Some((2.0, 0.0, 0.0, 3.0, 10.0, 20.0))).unwrap();
+        assert_eq!(maps(matrix, (1.0, 1.0)), (12.0, 23.0));
+
+        let both = user_transform(Some(90.0), Some((2.0, 0.0, 0.0, 2.0, 5.0, 0.0))).unwrap();
+        assert_eq!(maps(both, (1.0, 0.0)), (5.0, 2.0));
+    }
+
+    fn block(text: &str, size: f32) -> Block {
+        let font = crate::testing::superfont();
+        let line = crate::layout::layout_line(
+            text,
+            &font,
+            scale(size),
+            false,
+            crate::layout::Spacing::default(),
+            None,
+            crate::objects::Direction::Ltr,
+        );
+        Block::new(vec![line], 1.0, &crate::objects::TextAlign::Left, None)
+    }
+
+    fn style<'a>(font: &'a SuperFont<'static>, fill: &'a Paint<'static>) -> TextStyle<'a> {
+        TextStyle {
+            runs: vec![RunStyle {
+                font,
+                fill,
+                outline: None,
+            }],
+            outlines: &[],
+            transform: None,
+            shadow: None,
+            glow: None,
+            fade: None,
+            decorations: &[],
+            background: None,
+            highlights: Vec::new(),
+        }
+    }
+
+    /// How much of each column of the image was drawn over.
+    fn ink(im: &image::RgbaImage, background: image::Rgba<u8>) -> Vec<usize> {
+        (0..im.width())
+            .map(|x| {
+                (0..im.height())
+                    .filter(|&y| *im.get_pixel(x, y) != background)
+                    .count()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blocks_draw_within_their_bounds() {
+        let background = image::Rgba([10, 20, 30, 100]);
+        let mut im = image::RgbaImage::from_pixel(120, 60, background);
+        let block = block("HHH", 30.0);
+        let (font, fill) = (crate::testing::superfont(), Paint::default());
+
+        draw_block(&mut im, &block, 20.0, 10.0, 0.0, 0.0, &style(&font, &fill)).unwrap();
+
+        let ink = ink(&im, background);
+        let right = (20.0 + block.width).ceil() as usize + 1;
+        assert!(ink[..19].iter().chain(&ink[right..]).all(|&n| n == 0));
+        assert!(ink[20..right].iter().sum::<usize>() > 0);
+        for y in (0..9).chain((10 + block.height.ceil() as u32 + 1)..60) {
+            assert!((0..120).all(|x| *im.get_pixel(x, y) == background));
+        }
+    }
+
+    #[test]
+    fn shadows_and_backgrounds_widen_the_drawn_area() {
+        let background = image::Rgba([255, 255, 255, 255]);
+        let mut im = image::RgbaImage::from_pixel(160, 80, background);
+        let block = block("HHH", 30.0);
+        let (font, fill) = (crate::testing::superfont(), Paint::default());
+        let shadow = Shadow {
+            offset: (30.0, 0.0),
+            blur: 4.0,
+            paint: Paint::default(),
+        };
+        let mut highlight = Paint::default();
+        highlight.set_color_rgba8(255, 0, 0, 255);
+        let style = TextStyle {
+            shadow: Some(&shadow),
+            background: Some(Background {
+                paint: &highlight,
+                padding: (8.0, 8.0),
+                corner_radius: 0.0,
+                mode: BackgroundMode::Block,
+            }),
+            ..style(&font, &fill)
+        };
+
+        draw_block(&mut im, &block, 20.0, 20.0, 0.0, 0.0, &style).unwrap();
+
+        let ink = ink(&im, background);
+        let right = (20.0 + block.width) as usize;
+        // the padding around the text and the shadow past its end
+        assert!(ink[13] > 0 && ink[right + 20] > 0);
+        assert_eq!(im.get_pixel(14, 14).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn skipping_ink_breaks_underlines_around_descenders() {
+        let background = image::Rgba([255, 255, 255, 255]);
+        let block = block("gjpq", 40.0);
+        let (font, fill) = (crate::testing::superfont(), Paint::default());
+        let underline = |skip_ink| {
+            let decorations = [Decoration {
+                line: DecorationLine::Underline,
+                paint: None,
+                thickness: Some(2.0),
+                skip_ink,
+            }];
+            let mut im = image::RgbaImage::from_pixel(120, 60, background);
+            let style = TextStyle {
+                decorations: &decorations,
+                ..style(&font, &fill)
+            };
+            draw_block(&mut im, &block, 0.0, 0.0, 0.0, 0.0, &style).unwrap();
+
+            let (top, _) = decorations[0].metrics(&font, scale(40.0));
+            let y = (block.lines[0].0.ascent + top + 1.0) as u32;
+            (0..block.width as u32)
+                .filter(|&x| *im.get_pixel(x, y) == background)
+                .count()
+        };
+
+        assert_eq!(underline(false), 0);
+        assert!(underline(true) > 0);
+    }
+
+    #[test]
+    fn fades_thin_out_the_end_of_the_line() {
+        let background = image::Rgba([255, 255, 255, 255]);
+        let mut plain = image::RgbaImage::from_pixel(120, 60, background);
+        let mut faded = plain.clone();
+        let block = block("HHH", 30.0);
+        let (font, fill) = (crate::testing::superfont(), Paint::default());
+
+        draw_block(&mut plain, &block, 0.0, 0.0, 0.0, 0.0, &style(&font, &fill)).unwrap();
+        let fade = TextStyle {
+            fade: Some(block.width / 2.0),
+            ..style(&font, &fill)
+        };
+        draw_block(&mut faded, &block, 0.0, 0.0, 0.0, 0.0, &fade).unwrap();
+
+        let darkness = |im: &image::RgbaImage, columns: std::ops::Range<u32>| {
+            columns
+                .flat_map(|x| (0..60).map(move |y| (x, y)))
+                .map(|(x, y)| 255 - im.get_pixel(x, y).0[0] as u32)
+                .sum::<u32>()
+        };
+        let width = block.width as u32;
+        let start = 0..width / 2;
+        let end = width * 3 / 4..width;
+        assert_eq!(darkness(&plain, start.clone()), darkness(&faded, start));
+        assert!(darkness(&faded, end.clone()) < darkness(&plain, end) / 2);
+    }
+
+    #[test]
+    fn highlights_split_across_runs() {
+        let paint = || crate::paint::Paint(Paint::default());
+        let highlights = [(2, 7, paint()), (9, 10, paint()), (20, 30, paint())];
+        let runs: Vec<_> = run_highlights(&highlights, [4, 5, 3])
+            .into_iter()
+            .map(|h| (h.run, h.range))
+            .collect();
+        assert_eq!(runs, [(0, 2..4), (1, 0..3), (2, 0..1)]);
+
+        // a single run takes the ranges as they are
+        let runs: Vec<_> = run_highlights(&highlights, [usize::MAX])
+            .into_iter()
+            .map(|h| (h.run, h.range))
+            .collect();
+        assert_eq!(runs, [(0, 2..7), (0, 9..10), (0, 20..30)]);
+    }
+
+    #[test]
+    fn highlights_paint_behind_their_characters() {
+        let background = image::Rgba([255, 255, 255, 255]);
+        let mut im = image::RgbaImage::from_pixel(120, 60, background);
+        let block = block("a b c", 30.0);
+        let (font, fill) = (crate::testing::superfont(), Paint::default());
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(255, 0, 0, 255);
+        let glyphs = &block.lines[0].0.glyphs;
+        let (start, end) = (glyphs[2].x, glyphs[3].x + glyphs[3].advance);
+
+        let style = TextStyle {
+            highlights: vec![Highlight {
+                run: 0,
+                range: 2..4,
+                paint: &paint,
+            }],
+            ..style(&font, &fill)
+        };
+        draw_block(&mut im, &block, 0.0, 0.0, 0.0, 0.0, &style).unwrap();
+
+        let red = |x: f32| im.get_pixel(x as u32, 1).0 == [255, 0, 0, 255];
+        assert!(red(start + 1.0) && red(end - 1.0));
+        assert!(!red(start - 2.0) && !red(end + 2.0));
+    }
+
+    #[test]
+    fn emojis_fit_their_box() {
+        // wide emojis are scaled down to the box width and centered vertically
+        let place = place_emoji((40, 20), 5.0, 10.0, 20.0);
+        assert_eq!(maps(place, (0.0, 0.0)), (5.0, 15.0));
+        assert_eq!(maps(place, (40.0, 20.0)), (25.0, 25.0));
+
+        let place = place_emoji((10, 10), 0.0, 0.0, 30.0);
+        assert_eq!(maps(place, (10.0, 10.0)), (30.0, 30.0));
+    }
+}
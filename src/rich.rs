@@ -0,0 +1,321 @@
+use imagetext::prelude::*;
+use pyo3::prelude::*;
+
+use crate::bidi;
+use crate::discovery;
+use crate::font::Font;
+use crate::layout::{layout_run, wrap_glyphs, Line, RunMetrics, Spacing};
+use crate::objects::{Direction, Overflow, WrapStyle};
+use crate::render::RunStyle;
+use crate::shaping::{Features, Shaper};
+use crate::stroke::{Stroke, StrokeArg};
+
+/// A run of text sharing one font, size and paint.
+#[derive(Clone)]
+#[pyclass]
+pub struct Span {
+    pub text: String,
+    pub font: SuperFont<'static>,
+    pub size: f32,
+    pub fill: Paint<'static>,
+    pub stroke: Option<Stroke>,
+    pub stroke_color: Option<Paint<'static>>,
+    /// The default OpenType features of the span's font.
+    pub features: Features,
+}
+
+#[pymethods]
+impl Span {
+    #[new]
+    fn new(
+        text: String,
+        font: &Font,
+        size: f32,
+        fill: &crate::paint::Paint,
+        stroke: Option<StrokeArg>,
+        stroke_color: Option<&crate::paint::Paint>,
+    ) -> Self {
+        Span {
+            text,
+            font: font.0.clone(),
+            size,
+            fill: fill.0.clone(),
+            stroke: stroke.map(StrokeArg::into_stroke),
+            stroke_color: stroke_color.map(|c| c.0.clone()),
+            features: font.1.clone(),
+        }
+    }
+
+    #[getter]
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[getter]
+    fn size(&self) -> f32 {
+        self.size
+    }
+}
+
+impl Span {
+    pub fn style(&self) -> RunStyle<'_> {
+        RunStyle {
+            font: &self.font,
+            fill: &self.fill,
+            outline: self
+                .stroke
+                .as_ref()
+                .map(|s| (s, self.stroke_color.as_ref().unwrap_or(&BLACK))),
+        }
+    }
+}
+
+/// The spans with fallbacks found in the FontDB for the characters their
+/// fonts have no glyphs for, or None when auto fallbacks add none.
+pub fn discover_fallbacks(spans: &[Span], draw_emojis: bool) -> Option<Vec<Span>> {
+    let fonts: Vec<_> = spans
+        .iter()
+        .map(|span| discovery::extend(&span.font, &span.text, draw_emojis))
+        .collect();
+    if fonts.iter().all(Option::is_none) {
+        return None;
+    }
+
+    Some(
+        spans
+            .iter()
+            .zip(fonts)
+            .map(|(span, font)| Span {
+                font: font.unwrap_or_else(|| span.font.clone()),
+                ..span.clone()
+            })
+            .collect(),
+    )
+}
+
+/// Lays out spans as one paragraph, wrapping it to `width` when given.
+/// Each span is its own run, in order. Newlines start new paragraphs, each
+/// of them running in `direction`.
+pub fn layout_spans(
+    spans: &[Span],
+    width: Option<f32>,
+    draw_emojis: bool,
+    wrap_style: WrapStyle,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> Vec<Line> {
+    let metrics: Vec<_> = spans
+        .iter()
+        .map(|span| RunMetrics::new(&span.font, scale(span.size)))
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut glyphs = Vec::new();
+    let mut x = 0.0;
+    let mut fallback = match metrics.first() {
+        Some(metrics) => *metrics,
+        None => return lines,
+    };
+
+    // the bidi algorithm runs over whole paragraphs, across spans
+    let mut paragraphs = vec![String::new()];
+    for span in spans {
+        for (i, text) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                paragraphs.push(String::new());
+            }
+            if let Some(paragraph) = paragraphs.last_mut() {
+                paragraph.push_str(text);
+            }
+        }
+    }
+    let mut paragraphs = paragraphs.iter().map(|text| bidi::levels(text, direction));
+    let (mut levels, mut rtl) = paragraphs.next().unwrap_or_default();
+    // where the next piece of text starts in its paragraph
+    let mut position = 0;
+
+    for (run, span) in spans.iter().enumerate() {
+        let shaper = Shaper::with_defaults(shaper, &span.features);
+        let mut start = 0;
+        for (i, text) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                let wrapped = wrap_glyphs(
+                    std::mem::take(&mut glyphs),
+                    width,
+                    wrap_style,
+                    &metrics,
+                    fallback,
+                );
+                lines.extend(wrapped.into_iter().map(|line| Line { rtl, ..line }));
+                x = 0.0;
+                fallback = metrics[run];
+                (levels, rtl) = paragraphs.next().unwrap_or_default();
+                position = 0;
+            }
+
+            let (run_glyphs, end) = layout_run(
+                text,
+                &span.font,
+                scale(span.size),
+                draw_emojis,
+                spacing,
+                shaper.as_ref(),
+                levels.get(position..).unwrap_or_default(),
+                run,
+                x,
+            );
+            glyphs.extend(run_glyphs.into_iter().map(|(mut glyph, space)| {
+                glyph.index += start;
+                (glyph, space)
+            }));
+            start += text.chars().count() + 1;
+            position += text.chars().count();
+            x = end;
+        }
+    }
+
+    let wrapped = wrap_glyphs(glyphs, width, wrap_style, &metrics, fallback);
+    lines.extend(wrapped.into_iter().map(|line| Line { rtl, ..line }));
+    lines
+}
+
+/// Keeps the first `max_lines` lines, ending the last one with `ellipsis` in
+/// the style of its last run when overflowing with [`Overflow::Ellipsis`].
+/// Returns whether any lines were dropped.
+pub fn truncate_lines(
+    lines: &mut Vec<Line>,
+    spans: &[Span],
+    max_lines: usize,
+    overflow: Option<Overflow>,
+    ellipsis: &str,
+    width: Option<f32>,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+) -> bool {
+    if lines.len() <= max_lines {
+        return false;
+    }
+
+    lines.truncate(max_lines);
+    let line = match lines.last_mut() {
+        Some(line) if overflow == Some(Overflow::Ellipsis) => line,
+        _ => return true,
+    };
+
+    let run = line.glyphs.last().map_or(spans.len() - 1, |g| g.run);
+    let span = &spans[run];
+    let (glyphs, end) = layout_run(
+        ellipsis,
+        &span.font,
+        scale(span.size),
+        draw_emojis,
+        spacing,
+        Shaper::with_defaults(shaper, &span.features).as_ref(),
+        &[],
+        run,
+        0.0,
+    );
+
+    // letter spacing goes between the line and the ellipsis, not after it
+    let ellipsis_width = end - spacing.letter;
+    let width = width.unwrap_or(f32::INFINITY);
+    while let Some(last) = line.glyphs.last() {
+        // blank glyphs are whitespace, which never ends a line
+        if !last.is_blank() && last.x + last.advance + spacing.letter + ellipsis_width <= width {
+            break;
+        }
+        line.glyphs.pop();
+    }
+
+    let end = line
+        .glyphs
+        .last()
+        .map_or(0.0, |g| g.x + g.advance + spacing.letter);
+    let level = line.rtl as u8;
+    line.glyphs.extend(glyphs.into_iter().map(|(mut glyph, _)| {
+        glyph.x += end;
+        // the ellipsis isn't part of the text, it ends the line in the
+        // paragraph's direction
+        glyph.index = usize::MAX;
+        glyph.level = level;
+        glyph
+    }));
+    line.width = end + ellipsis_width;
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::superfont;
+
+    fn span(text: &str, size: f32) -> Span {
+        Span {
+            text: text.to_string(),
+            font: superfont(),
+            size,
+            fill: Paint::default(),
+            stroke: None,
+            stroke_color: None,
+            features: Features::default(),
+        }
+    }
+
+    fn layout(spans: &[Span], width: Option<f32>) -> Vec<Line> {
+        layout_spans(
+            spans,
+            width,
+            false,
+            WrapStyle::Word,
+            Spacing::default(),
+            None,
+            Direction::Ltr,
+        )
+    }
+
+    #[test]
+    fn spans_share_a_line() {
+        let spans = [span("ab ", 10.0), span("cd", 20.0)];
+        let lines = layout(&spans, None);
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        let runs: Vec<_> = line.glyphs.iter().map(|g| (g.run, g.index)).collect();
+        assert_eq!(runs, [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1)]);
+        assert!(line.glyphs.windows(2).all(|g| g[0].x < g[1].x));
+
+        // the largest run sets the line's metrics
+        let large = RunMetrics::new(&spans[1].font, scale(20.0));
+        assert_eq!(line.size, 20.0);
+        assert_eq!((line.ascent, line.descent), (large.ascent, large.descent));
+    }
+
+    #[test]
+    fn newlines_split_paragraphs_across_spans() {
+        let spans = [span("ab\nc", 10.0), span("d\ne", 20.0)];
+        let lines = layout(&spans, None);
+        let text: Vec<Vec<_>> = lines
+            .iter()
+            .map(|line| line.glyphs.iter().map(|g| (g.run, g.index)).collect())
+            .collect();
+        assert_eq!(
+            text,
+            [vec![(0, 0), (0, 1)], vec![(0, 3), (1, 0)], vec![(1, 2)]]
+        );
+        assert_eq!(lines[0].size, 10.0);
+        assert_eq!(lines[2].size, 20.0);
+    }
+
+    #[test]
+    fn spans_wrap_as_one_paragraph() {
+        let spans = [span("aaa bbb ", 20.0), span("ccc", 20.0)];
+        let full = layout(&spans, None)[0].width;
+        let lines = layout(&spans, Some(full * 0.8));
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].glyphs[0].run, 1);
+        assert_eq!(lines[1].glyphs[0].x, 0.0);
+    }
+}
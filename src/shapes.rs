@@ -0,0 +1,99 @@
+use pyo3::prelude::*;
+use tiny_skia::PathBuilder;
+
+#[pyclass]
+pub struct Path(pub PathBuilder);
+
+#[pymethods]
+impl Path {
+    #[new]
+    fn new() -> Self {
+        Path(PathBuilder::new())
+    }
+
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.quad_to(x1, y1, x, y);
+    }
+
+    fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.0.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+pub fn rect(x: f32, y: f32, width: f32, height: f32) -> Option<tiny_skia::Path> {
+    tiny_skia::Rect::from_xywh(x, y, width, height).map(PathBuilder::from_rect)
+}
+
+pub fn rounded_rect(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    radius: f32,
+) -> Option<tiny_skia::Path> {
+    let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+    if radius == 0.0 {
+        return rect(x, y, width, height);
+    }
+
+    // control point distance for approximating a quarter circle with a cubic
+    let k = radius * 0.552_284_8;
+    let (right, bottom) = (x + width, y + height);
+
+    let mut pb = PathBuilder::new();
+    pb.move_to(x + radius, y);
+    pb.line_to(right - radius, y);
+    pb.cubic_to(
+        right - radius + k,
+        y,
+        right,
+        y + radius - k,
+        right,
+        y + radius,
+    );
+    pb.line_to(right, bottom - radius);
+    pb.cubic_to(
+        right,
+        bottom - radius + k,
+        right - radius + k,
+        bottom,
+        right - radius,
+        bottom,
+    );
+    pb.line_to(x + radius, bottom);
+    pb.cubic_to(
+        x + radius - k,
+        bottom,
+        x,
+        bottom - radius + k,
+        x,
+        bottom - radius,
+    );
+    pb.line_to(x, y + radius);
+    pb.cubic_to(x, y + radius - k, x + radius - k, y, x + radius, y);
+    pb.close();
+    pb.finish()
+}
+
+pub fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<tiny_skia::Path> {
+    tiny_skia::Rect::from_ltrb(cx - rx, cy - ry, cx + rx, cy + ry).and_then(PathBuilder::from_oval)
+}
+
+pub fn line(x0: f32, y0: f32, x1: f32, y1: f32) -> Option<tiny_skia::Path> {
+    let mut pb = PathBuilder::new();
+    pb.move_to(x0, y0);
+    pb.line_to(x1, y1);
+    pb.finish()
+}
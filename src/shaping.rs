@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+
+use imagetext::prelude::*;
+use pyo3::prelude::*;
+
+use crate::bidi;
+use crate::font::face;
+
+/// Shapes text with OpenType tables instead of placing glyphs one by one,
+/// so scripts that join, reorder or form ligatures render correctly.
+#[derive(Clone, Default)]
+pub struct Shaper {
+    features: Vec<rustybuzz::Feature>,
+}
+
+/// OpenType features turned on or off, given as a dict of tags to values,
+/// like `{"tnum": 1, "liga": 0}`.
+#[derive(Clone, Default)]
+pub struct Features(pub Vec<rustybuzz::Feature>);
+
+impl<'source> FromPyObject<'source> for Features {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let features: BTreeMap<String, u32> = ob.extract()?;
+        features
+            .into_iter()
+            .map(|(tag, value)| {
+                Ok(rustybuzz::Feature::new(
+                    parse_tag(&tag, "feature")?,
+                    value,
+                    ..,
+                ))
+            })
+            .collect::<PyResult<_>>()
+            .map(Features)
+    }
+}
+
+/// Parses a four character OpenType tag, like `liga` or `wght`.
+pub fn parse_tag(tag: &str, kind: &str) -> PyResult<rustybuzz::Tag> {
+    let bytes: [u8; 4] = tag
+        .as_bytes()
+        .try_into()
+        .ok()
+        .filter(|bytes: &[u8; 4]| bytes.iter().all(|b| (b' '..=b'~').contains(b)))
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid OpenType {} tag: {:?}",
+                kind, tag
+            ))
+        })?;
+    Ok(rustybuzz::Tag::from_bytes(&bytes))
+}
+
+/// A glyph placed by the shaper, in pixels.
+pub struct ShapedGlyph {
+    pub id: rusttype::GlyphId,
+    /// The byte offset of the first character the glyph was shaped from.
+    pub cluster: usize,
+    pub advance: f32,
+    pub offset: (f32, f32),
+}
+
+impl Shaper {
+    /// A shaper when shaping is asked for or there are features to apply.
+    /// A font's default features come first, so that the features given
+    /// along with them override them.
+    pub fn new(
+        shaping: Option<bool>,
+        defaults: &Features,
+        features: Option<&Features>,
+    ) -> Option<Self> {
+        let features: Vec<_> = defaults
+            .0
+            .iter()
+            .chain(features.iter().flat_map(|f| f.0.iter()))
+            .copied()
+            .collect();
+
+        (shaping.unwrap_or(false) || !features.is_empty()).then_some(Shaper { features })
+    }
+
+    /// The shaper for a run drawn with a font with its own default features.
+    pub fn with_defaults(shaper: Option<&Shaper>, defaults: &Features) -> Option<Self> {
+        match shaper {
+            Some(shaper) => {
+                let features = defaults.0.iter().chain(&shaper.features).copied();
+                Some(Shaper {
+                    features: features.collect(),
+                })
+            }
+            None => Shaper::new(None, defaults, None),
+        }
+    }
+
+    /// Shapes `text` with a single font, without fallbacks, in the given
+    /// direction. Right to left text comes out in visual order. Fonts the
+    /// shaper can't read have their glyphs placed one by one instead.
+    pub fn shape(
+        &self,
+        font: &rusttype::Font<'static>,
+        scale: Scale,
+        text: &str,
+        rtl: bool,
+    ) -> Vec<ShapedGlyph> {
+        // the font's tables are parsed already, only their layout is read here
+        let Some(face) = rustybuzz::Face::from_face(face(font).clone()) else {
+            return unshaped(font, scale, text, rtl);
+        };
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.set_direction(if rtl {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+        buffer.guess_segment_properties();
+        let shaped = rustybuzz::shape(&face, &self.features, buffer);
+
+        let sx = font.scale_for_pixel_height(scale.x);
+        let sy = font.scale_for_pixel_height(scale.y);
+
+        shaped
+            .glyph_infos()
+            .iter()
+            .zip(shaped.glyph_positions())
+            .map(|(info, pos)| ShapedGlyph {
+                id: rusttype::GlyphId(info.glyph_id as u16),
+                cluster: info.cluster as usize,
+                advance: pos.x_advance as f32 * sx,
+                // font units grow upwards
+                offset: (pos.x_offset as f32 * sx, -pos.y_offset as f32 * sy),
+            })
+            .collect()
+    }
+}
+
+/// Glyphs for each character of `text`, placed by their advances.
+fn unshaped(
+    font: &rusttype::Font<'static>,
+    scale: Scale,
+    text: &str,
+    rtl: bool,
+) -> Vec<ShapedGlyph> {
+    let mut glyphs: Vec<_> = text
+        .char_indices()
+        .map(|(cluster, c)| {
+            let glyph = font.glyph(bidi::mirror(c, rtl as u8)).scaled(scale);
+            ShapedGlyph {
+                id: glyph.id(),
+                cluster,
+                advance: glyph.h_metrics().advance_width,
+                offset: (0.0, 0.0),
+            }
+        })
+        .collect();
+    if rtl {
+        glyphs.reverse();
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::font;
+
+    #[test]
+    fn shaping_keeps_every_character() {
+        let shaper = Shaper::default();
+        let font = font();
+        let scale = Scale::uniform(20.0);
+        let shaped = shaper.shape(&font, scale, "abc", false);
+        let clusters: Vec<_> = shaped.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [0, 1, 2]);
+        assert!(shaped
+            .iter()
+            .all(|g| g.id == font.glyph(g.id).id() && g.advance > 0.0));
+
+        let rtl = shaper.shape(&font, scale, "abc", true);
+        let clusters: Vec<_> = rtl.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [2, 1, 0]);
+    }
+
+    #[test]
+    fn unshaped_glyphs_follow_the_font() {
+        let font = font();
+        let scale = Scale::uniform(20.0);
+        let glyphs = unshaped(&font, scale, "aé", true);
+        let clusters: Vec<_> = glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, [1, 0]);
+
+        let a = font.glyph('a').scaled(scale);
+        assert_eq!(glyphs[1].id, a.id());
+        assert_eq!(glyphs[1].advance, a.h_metrics().advance_width);
+    }
+
+    fn error(err: PyErr) -> String {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| err.value(py).to_string())
+    }
+
+    #[test]
+    fn tags_are_four_printable_characters() {
+        assert_eq!(
+            parse_tag("liga", "feature").unwrap(),
+            rustybuzz::Tag::from_bytes(b"liga")
+        );
+        assert_eq!(
+            parse_tag("cv1 ", "feature").unwrap(),
+            rustybuzz::Tag::from_bytes(b"cv1 ")
+        );
+        assert_eq!(
+            error(parse_tag("ligature", "feature").unwrap_err()),
+            "Invalid OpenType feature tag: \"ligature\""
+        );
+        assert_eq!(
+            error(parse_tag("wgh\n", "variation axis").unwrap_err()),
+            "Invalid OpenType variation axis tag: \"wgh\\n\""
+        );
+        assert!(parse_tag("wgé", "feature").is_err());
+    }
+
+    #[test]
+    fn features_are_read_from_dicts() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dict = pyo3::types::PyDict::new(py);
+            dict.set_item("tnum", 1).unwrap();
+            dict.set_item("liga", 0).unwrap();
+            let features: Features = dict.extract().unwrap();
+            let features: Vec<_> = features.0.iter().map(|f| (f.tag, f.value)).collect();
+            assert_eq!(
+                features,
+                [
+                    (rustybuzz::Tag::from_bytes(b"liga"), 0),
+                    (rustybuzz::Tag::from_bytes(b"tnum"), 1),
+                ]
+            );
+
+            dict.set_item("toolong", 1).unwrap();
+            assert!(dict.extract::<Features>().is_err());
+        });
+    }
+
+    #[test]
+    fn shapers_are_made_for_shaping_or_features() {
+        let kern = Features(vec![rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"kern"),
+            0,
+            ..,
+        )]);
+        let none = Features::default();
+        assert!(Shaper::new(None, &none, None).is_none());
+        assert!(Shaper::new(Some(false), &none, Some(&none)).is_none());
+        assert!(Shaper::new(Some(true), &none, None).is_some());
+        assert!(Shaper::new(None, &kern, None).is_some());
+        assert!(Shaper::new(None, &none, Some(&kern)).is_some());
+        assert!(Shaper::with_defaults(None, &none).is_none());
+
+        // the features given after a font's defaults override them
+        let on = Features(vec![rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"kern"),
+            1,
+            ..,
+        )]);
+        let shaper = Shaper::new(None, &kern, Some(&on)).unwrap();
+        let values: Vec<_> = shaper.features.iter().map(|f| f.value).collect();
+        assert_eq!(values, [0, 1]);
+    }
+
+    #[test]
+    fn features_change_the_shaping() {
+        let font = font();
+        let scale = Scale::uniform(40.0);
+        let width = |shaper: &Shaper| -> f32 {
+            shaper
+                .shape(&font, scale, "AVAT", false)
+                .iter()
+                .map(|g| g.advance)
+                .sum()
+        };
+        let kern = Features(vec![rustybuzz::Feature::new(
+            rustybuzz::Tag::from_bytes(b"kern"),
+            0,
+            ..,
+        )]);
+        let unkerned = Shaper::new(None, &kern, None).unwrap();
+        assert!(width(&Shaper::default()) < width(&unkerned));
+    }
+}
@@ -0,0 +1,234 @@
+use pyo3::prelude::*;
+use tiny_skia::{FillRule, Mask, Paint, Path, PixmapMut, StrokeDash, Transform};
+
+use crate::objects::{LineCap, LineJoin, StrokePlacement};
+
+#[derive(Clone)]
+#[pyclass]
+pub struct Stroke {
+    pub stroke: tiny_skia::Stroke,
+    pub placement: StrokePlacement,
+}
+
+#[pymethods]
+impl Stroke {
+    #[new]
+    fn new(
+        width: f32,
+        join: Option<LineJoin>,
+        cap: Option<LineCap>,
+        miter_limit: Option<f32>,
+        dash: Option<Vec<f32>>,
+        dash_offset: Option<f32>,
+        placement: Option<StrokePlacement>,
+    ) -> PyResult<Self> {
+        let mut stroke = imagetext::drawing::utils::stroke(width);
+
+        if let Some(join) = join {
+            stroke.line_join = join.to_line_join();
+        }
+        if let Some(cap) = cap {
+            stroke.line_cap = cap.to_line_cap();
+        }
+        if let Some(miter_limit) = miter_limit {
+            stroke.miter_limit = miter_limit;
+        }
+        if let Some(dash) = dash {
+            stroke.dash = Some(
+                StrokeDash::new(dash, dash_offset.unwrap_or(0.0)).ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "Invalid dash pattern, expected an even number of positive lengths",
+                    )
+                })?,
+            );
+        }
+
+        Ok(Stroke {
+            stroke,
+            placement: placement.unwrap_or(StrokePlacement::Center),
+        })
+    }
+
+    #[getter]
+    fn width(&self) -> f32 {
+        self.stroke.width
+    }
+
+    #[getter]
+    fn miter_limit(&self) -> f32 {
+        self.stroke.miter_limit
+    }
+
+    #[getter]
+    fn placement(&self) -> StrokePlacement {
+        self.placement
+    }
+}
+
+impl Stroke {
+    pub fn from_width(width: f32) -> Self {
+        Stroke {
+            stroke: imagetext::drawing::utils::stroke(width),
+            placement: StrokePlacement::Center,
+        }
+    }
+}
+
+/// A stroke given either as a width or as a `Stroke`.
+#[derive(FromPyObject)]
+pub enum StrokeArg {
+    Stroke(Stroke),
+    Width(f32),
+}
+
+impl StrokeArg {
+    pub fn into_stroke(self) -> Stroke {
+        match self {
+            StrokeArg::Stroke(stroke) => stroke,
+            StrokeArg::Width(width) => Stroke::from_width(width),
+        }
+    }
+}
+
+/// Converts outline layers into strokes, widest first so that each layer
+/// is drawn over the ones outside of it.
+pub fn outline_layers(
+    layers: Option<Vec<(StrokeArg, crate::paint::Paint)>>,
+) -> Vec<(Stroke, Paint<'static>)> {
+    let mut layers: Vec<_> = layers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(stroke, paint)| (stroke.into_stroke(), paint.0))
+        .collect();
+    layers.sort_by(|(a, _), (b, _)| b.stroke.width.total_cmp(&a.stroke.width));
+    layers
+}
+
+/// Multiplies `mask` by `clip`, keeping only what's inside both.
+pub fn intersect_mask(mask: &mut Mask, clip: &Mask) {
+    for (m, c) in mask.data_mut().iter_mut().zip(clip.data()) {
+        *m = ((*m as u16 * *c as u16 + 127) / 255) as u8;
+    }
+}
+
+/// Strokes `path`, keeping the stroke outside or inside of its filled area
+/// when asked to.
+pub fn stroke_path(
+    pixmap: &mut PixmapMut,
+    path: &Path,
+    paint: &Paint,
+    stroke: &Stroke,
+    transform: Transform,
+    clip: Option<&Mask>,
+) {
+    if stroke.placement == StrokePlacement::Center {
+        pixmap.stroke_path(path, paint, &stroke.stroke, transform, clip);
+        return;
+    }
+
+    let mut mask = match Mask::new(pixmap.width(), pixmap.height()) {
+        Some(mask) => mask,
+        None => return,
+    };
+    mask.fill_path(path, FillRule::Winding, paint.anti_alias, transform);
+    if stroke.placement == StrokePlacement::Outside {
+        mask.invert();
+    }
+    if let Some(clip) = clip {
+        intersect_mask(&mut mask, clip);
+    }
+
+    // half of a stroke twice as wide lands on each side of the outline
+    let doubled = tiny_skia::Stroke {
+        width: stroke.stroke.width * 2.0,
+        ..stroke.stroke.clone()
+    };
+    pixmap.stroke_path(path, paint, &doubled, transform, Some(&mask));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroked(placement: StrokePlacement) -> tiny_skia::Pixmap {
+        let mut pixmap = tiny_skia::Pixmap::new(30, 30).unwrap();
+        let square = tiny_skia::Rect::from_xywh(10.0, 10.0, 10.0, 10.0).unwrap();
+        let stroke = Stroke {
+            stroke: tiny_skia::Stroke {
+                width: 4.0,
+                ..Default::default()
+            },
+            placement,
+        };
+
+        stroke_path(
+            &mut pixmap.as_mut(),
+            &tiny_skia::PathBuilder::from_rect(square),
+            &Paint::default(),
+            &stroke,
+            Transform::identity(),
+            None,
+        );
+        pixmap
+    }
+
+    /// Whether the pixels just inside and just outside of the left edge
+    /// of the square are drawn.
+    fn sides(pixmap: &tiny_skia::Pixmap) -> (bool, bool) {
+        let drawn = |x| pixmap.pixel(x, 15).unwrap().alpha() > 0;
+        (drawn(11), drawn(8))
+    }
+
+    #[test]
+    fn placement_picks_the_side_of_the_outline() {
+        assert_eq!(sides(&stroked(StrokePlacement::Center)), (true, true));
+        assert_eq!(sides(&stroked(StrokePlacement::Inside)), (true, false));
+        assert_eq!(sides(&stroked(StrokePlacement::Outside)), (false, true));
+
+        // placed strokes keep their full width on their side
+        let outside = stroked(StrokePlacement::Outside);
+        assert!(outside.pixel(6, 15).unwrap().alpha() > 0);
+        assert_eq!(outside.pixel(5, 15).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn outline_layers_go_widest_first() {
+        let layer = |width: f32, red: u8| {
+            let stroke = Stroke {
+                stroke: tiny_skia::Stroke {
+                    width,
+                    ..Default::default()
+                },
+                placement: StrokePlacement::Center,
+            };
+            let mut paint = Paint::default();
+            paint.set_color_rgba8(red, 0, 0, 255);
+            (StrokeArg::Stroke(stroke), crate::paint::Paint(paint))
+        };
+
+        let layers = outline_layers(Some(vec![layer(2.0, 1), layer(8.0, 2), layer(4.0, 3)]));
+        let order: Vec<_> = layers
+            .iter()
+            .map(|(s, p)| {
+                let tiny_skia::Shader::SolidColor(color) = p.shader else {
+                    unreachable!()
+                };
+                (s.stroke.width, color.to_color_u8().red())
+            })
+            .collect();
+        assert_eq!(order, [(8.0, 2), (4.0, 3), (2.0, 1)]);
+
+        assert!(outline_layers(None).is_empty());
+    }
+
+    #[test]
+    fn intersecting_masks_multiplies_them() {
+        let mut mask = Mask::new(2, 1).unwrap();
+        mask.data_mut().copy_from_slice(&[255, 128]);
+        let mut clip = Mask::new(2, 1).unwrap();
+        clip.data_mut().copy_from_slice(&[128, 0]);
+
+        intersect_mask(&mut mask, &clip);
+        assert_eq!(mask.data(), [128, 0]);
+    }
+}
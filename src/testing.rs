@@ -0,0 +1,44 @@
+//! Fonts shared by the tests.
+
+use imagetext::prelude::*;
+
+/// Jellee Roman, a small Latin font under the SIL Open Font License.
+pub const FONT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fonts/Jellee-Roman.ttf");
+
+pub fn font() -> Font<'static> {
+    crate::font::font_from_vec(std::fs::read(FONT_PATH).unwrap(), 0).unwrap()
+}
+
+pub fn superfont() -> SuperFont<'static> {
+    SuperFont::new(font(), Vec::new())
+}
+
+/// A font collection of `faces` copies of the test font.
+pub fn collection(faces: u32) -> Vec<u8> {
+    let font = std::fs::read(FONT_PATH).unwrap();
+    let u16_at = |at: usize| u16::from_be_bytes([font[at], font[at + 1]]);
+    let u32_at = |at: usize| u32::from_be_bytes(font[at..at + 4].try_into().unwrap());
+    let tables = u16_at(4) as usize;
+
+    let header = 12 + 4 * faces as usize;
+    let size = (font.len() + 3) & !3;
+    let mut data = b"ttcf".to_vec();
+    data.extend(0x0001_0000u32.to_be_bytes());
+    data.extend(faces.to_be_bytes());
+    for face in 0..faces as usize {
+        data.extend(((header + face * size) as u32).to_be_bytes());
+    }
+
+    // table offsets count from the start of the file
+    for _ in 0..faces {
+        let start = data.len();
+        data.extend(&font);
+        data.resize(start + size, 0);
+        for table in 0..tables {
+            let at = 12 + table * 16 + 8;
+            let offset = u32_at(at) + start as u32;
+            data[start + at..start + at + 4].copy_from_slice(&offset.to_be_bytes());
+        }
+    }
+    data
+}
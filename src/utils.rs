@@ -1,6 +1,12 @@
 use pyo3::prelude::*;
 
-use crate::{font::Font, objects::WrapStyle};
+use crate::{
+    font::Font,
+    layout::{emoji_text, layout_line, wrap_text, Block, Spacing, EMOJI_PLACEHOLDER},
+    objects::{Direction, Overflow, TextAlign, WrapStyle},
+    rich::{discover_fallbacks, layout_spans, Span},
+    shaping::{Features, Shaper},
+};
 use imagetext::prelude::*;
 
 #[pyfunction]
@@ -15,13 +21,34 @@ pub fn text_size(
     size: f32,
     font: &Font,
     draw_emojis: Option<bool>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
 ) -> (i32, i32) {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
     py.allow_threads(|| {
-        if draw_emojis.unwrap_or(false) {
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let (width, height) = if draw_emojis.unwrap_or(false) {
             imagetext::measure::text_size_with_emojis(scale(size), &font.0, text)
         } else {
             imagetext::measure::text_size(scale(size), &font.0, text)
-        }
+        };
+        let width = line_width(
+            text,
+            width,
+            size,
+            font,
+            draw_emojis.unwrap_or(false),
+            spacing,
+            shaper.as_ref(),
+            direction,
+        );
+        (width, height)
     })
 }
 
@@ -33,26 +60,329 @@ pub fn text_size_multiline(
     font: &Font,
     line_spacing: Option<f32>,
     draw_emojis: Option<bool>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
 ) -> (i32, i32) {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
     py.allow_threads(|| {
-        if draw_emojis.unwrap_or(false) {
-            imagetext::measure::text_size_multiline_with_emojis(
-                &lines,
-                &font.0,
-                scale(size),
-                line_spacing.unwrap_or(1.0),
+        let discovered = font.discover(&lines.concat(), draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        lines_size(
+            &lines,
+            size,
+            font,
+            line_spacing.unwrap_or(1.0),
+            draw_emojis.unwrap_or(false),
+            spacing,
+            shaper.as_ref(),
+            direction,
+        )
+    })
+}
+
+/// The size of `lines` drawn together, wide enough for the widest line with
+/// its spacing.
+fn lines_size(
+    lines: &[String],
+    size: f32,
+    font: &Font,
+    line_spacing: f32,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> (i32, i32) {
+    let (width, height) = if draw_emojis {
+        imagetext::measure::text_size_multiline_with_emojis(
+            lines,
+            &font.0,
+            scale(size),
+            line_spacing,
+        )
+    } else {
+        imagetext::measure::text_size_multiline(lines, &font.0, scale(size), line_spacing)
+    };
+
+    if spacing.is_none() && shaper.is_none() {
+        return (width, height);
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| {
+            let (width, _) = if draw_emojis {
+                imagetext::measure::text_size_with_emojis(scale(size), &font.0, line)
+            } else {
+                imagetext::measure::text_size(scale(size), &font.0, line)
+            };
+            line_width(
+                line,
+                width,
+                size,
+                font,
+                draw_emojis,
+                spacing,
+                shaper,
+                direction,
             )
-        } else {
-            imagetext::measure::text_size_multiline(
-                &lines,
+        })
+        .max()
+        .unwrap_or(width);
+    (width, height)
+}
+
+/// The width of a single line of text, given the width imagetext measured.
+/// Shaped text is measured the way it's laid out instead.
+fn line_width(
+    text: &str,
+    measured: i32,
+    size: f32,
+    font: &Font,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> i32 {
+    match shaper {
+        Some(shaper) => {
+            let line = layout_line(
+                text,
                 &font.0,
                 scale(size),
-                line_spacing.unwrap_or(1.0),
-            )
+                draw_emojis,
+                spacing,
+                Some(shaper),
+                direction,
+            );
+            line.width.ceil() as i32
         }
+        None => measured + spacing_width(text, font, draw_emojis, spacing).ceil() as i32,
+    }
+}
+
+/// The width letter and word spacing add to a single line of text.
+fn spacing_width(text: &str, font: &Font, draw_emojis: bool, spacing: Spacing) -> f32 {
+    if spacing.is_none() {
+        return 0.0;
+    }
+
+    if draw_emojis {
+        // each emoji counts as one character
+        let (text, _) = imagetext::emoji::parse::parse_out_emojis(
+            text,
+            font.0.emoji_options.parse_shortcodes,
+            font.0.emoji_options.parse_discord_emojis,
+        );
+        spacing.extra_width(&text)
+    } else {
+        spacing.extra_width(text)
+    }
+}
+
+#[pyfunction]
+pub fn text_lines_extents(
+    py: Python,
+    lines: Vec<String>,
+    size: f32,
+    font: &Font,
+    width: Option<f32>,
+    line_spacing: Option<f32>,
+    align: Option<&TextAlign>,
+    draw_emojis: Option<bool>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
+) -> Vec<(f32, f32, f32, f32)> {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
+    py.allow_threads(|| {
+        let discovered = font.discover(&lines.concat(), draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let lines = lines
+            .iter()
+            .map(|line| {
+                layout_line(
+                    line,
+                    &font.0,
+                    scale(size),
+                    draw_emojis.unwrap_or(false),
+                    spacing,
+                    shaper.as_ref(),
+                    direction,
+                )
+            })
+            .collect();
+        let block = Block::new(
+            lines,
+            line_spacing.unwrap_or(1.0),
+            align.unwrap_or(&TextAlign::Left),
+            width,
+        );
+
+        block
+            .lines
+            .iter()
+            .map(|(line, x, y)| (*x, *y, line.width, line.ascent - line.descent))
+            .collect()
     })
 }
 
+#[pyfunction]
+pub fn rich_text_size(
+    py: Python,
+    spans: Vec<Span>,
+    width: Option<f32>,
+    line_spacing: Option<f32>,
+    draw_emojis: Option<bool>,
+    wrap_style: Option<WrapStyle>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
+) -> (i32, i32) {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &Features::default(), features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
+    py.allow_threads(|| {
+        let discovered = discover_fallbacks(&spans, draw_emojis.unwrap_or(false));
+        let spans = discovered.as_deref().unwrap_or(&spans);
+        let lines = layout_spans(
+            spans,
+            width,
+            draw_emojis.unwrap_or(false),
+            wrap_style.unwrap_or(WrapStyle::Word),
+            spacing,
+            shaper.as_ref(),
+            direction,
+        );
+        let block = Block::new(lines, line_spacing.unwrap_or(1.0), &TextAlign::Left, None);
+
+        (block.width.ceil() as i32, block.height.ceil() as i32)
+    })
+}
+
+#[pyfunction]
+pub fn fit_text(
+    py: Python,
+    text: &str,
+    font: &Font,
+    box_width: f32,
+    box_height: f32,
+    min_size: Option<f32>,
+    max_size: Option<f32>,
+    line_spacing: Option<f32>,
+    wrap_style: Option<WrapStyle>,
+    draw_emojis: Option<bool>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
+) -> (f32, Vec<String>) {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
+    py.allow_threads(|| {
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        fit_lines(
+            text,
+            font,
+            box_width,
+            box_height,
+            min_size,
+            max_size,
+            line_spacing,
+            wrap_style,
+            draw_emojis,
+            spacing,
+            shaper.as_ref(),
+            direction,
+        )
+    })
+}
+
+/// Finds the largest size between `min_size` and `max_size` at which the
+/// wrapped text fits the box, falling back to `min_size` when none do.
+pub fn fit_lines(
+    text: &str,
+    font: &Font,
+    box_width: f32,
+    box_height: f32,
+    min_size: Option<f32>,
+    max_size: Option<f32>,
+    line_spacing: Option<f32>,
+    wrap_style: Option<WrapStyle>,
+    draw_emojis: Option<bool>,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> (f32, Vec<String>) {
+    let line_spacing = line_spacing.unwrap_or(1.0);
+    let layout = |size: f32| {
+        let lines = wrap_lines(
+            text,
+            box_width as i32,
+            size,
+            font,
+            draw_emojis,
+            wrap_style,
+            spacing,
+            shaper,
+            direction,
+        );
+        let (width, height) = lines_size(
+            &lines,
+            size,
+            font,
+            line_spacing,
+            draw_emojis.unwrap_or(false),
+            spacing,
+            shaper,
+            direction,
+        );
+        let fits = width as f32 <= box_width && height as f32 <= box_height;
+        (fits, lines)
+    };
+
+    let min_size = min_size.unwrap_or(1.0).max(0.0);
+    let max_size = max_size.unwrap_or(box_height).max(min_size);
+    fit_size(min_size, max_size, layout)
+}
+
+/// Searches for the largest size between `low` and `high` that `layout`
+/// says fits, returning it with its layout, or `low` when none fit.
+pub fn fit_size<T>(mut low: f32, mut high: f32, layout: impl Fn(f32) -> (bool, T)) -> (f32, T) {
+    let (fits, lines) = layout(high);
+    if fits {
+        return (high, lines);
+    }
+
+    let (_, mut best) = layout(low);
+    // sizes closer than this render the same
+    while high - low > 0.1 {
+        let size = (low + high) / 2.0;
+        match layout(size) {
+            (true, lines) => {
+                low = size;
+                best = lines;
+            }
+            (false, _) => high = size,
+        }
+    }
+
+    (low, best)
+}
+
 #[pyfunction]
 pub fn text_wrap(
     py: Python,
@@ -62,49 +392,378 @@ pub fn text_wrap(
     font: &Font,
     draw_emojis: Option<bool>,
     wrap_style: Option<WrapStyle>,
+    max_lines: Option<usize>,
+    overflow: Option<Overflow>,
+    ellipsis: Option<&str>,
+    letter_spacing: Option<f32>,
+    word_spacing: Option<f32>,
+    shaping: Option<bool>,
+    direction: Option<Direction>,
+    features: Option<Features>,
 ) -> Vec<String> {
+    let spacing = Spacing::new(letter_spacing, word_spacing);
+    let shaper = Shaper::new(shaping, &font.1, features.as_ref());
+    let direction = direction.unwrap_or(Direction::Auto);
     py.allow_threads(|| {
-        if draw_emojis.unwrap_or(false) {
-            let (text, emojis) = imagetext::emoji::parse::parse_out_emojis(
-                text,
-                font.0.emoji_options.parse_shortcodes,
-                font.0.emoji_options.parse_discord_emojis,
-            );
+        let discovered = font.discover(text, draw_emojis.unwrap_or(false));
+        let font = discovered.as_ref().unwrap_or(font);
+        let lines = wrap_lines(
+            text,
+            width,
+            size,
+            font,
+            draw_emojis,
+            wrap_style,
+            spacing,
+            shaper.as_ref(),
+            direction,
+        );
+        match max_lines {
+            Some(max_lines) => {
+                truncate_lines(
+                    lines,
+                    max_lines,
+                    overflow,
+                    ellipsis,
+                    width as f32,
+                    size,
+                    font,
+                    draw_emojis,
+                    spacing,
+                    shaper.as_ref(),
+                    direction,
+                )
+                .0
+            }
+            None => lines,
+        }
+    })
+}
+
+pub const DEFAULT_ELLIPSIS: &str = "…";
+
+/// Keeps the first `max_lines` lines, ending the last one with `ellipsis`
+/// when overflowing with [`Overflow::Ellipsis`]. Also returns whether any
+/// lines were dropped.
+pub fn truncate_lines(
+    mut lines: Vec<String>,
+    max_lines: usize,
+    overflow: Option<Overflow>,
+    ellipsis: Option<&str>,
+    width: f32,
+    size: f32,
+    font: &Font,
+    draw_emojis: Option<bool>,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> (Vec<String>, bool) {
+    if lines.len() <= max_lines {
+        return (lines, false);
+    }
 
-            let mut lines = imagetext::wrap::text_wrap(
-                &text,
+    lines.truncate(max_lines);
+    if overflow == Some(Overflow::Ellipsis) {
+        if let Some(last) = lines.last_mut() {
+            *last = ellipsize(
+                last,
+                ellipsis.unwrap_or(DEFAULT_ELLIPSIS),
                 width,
-                &font.0,
-                scale(size),
-                wrap_style.unwrap_or(WrapStyle::Word).to_wrap_style(),
-                text_width_with_emojis,
+                size,
+                font,
+                draw_emojis.unwrap_or(false),
+                spacing,
+                shaper,
+                direction,
             );
+        }
+    }
 
-            let mut emojis_iter = emojis.iter();
-            lines.iter_mut().for_each(|line| {
-                let found = line.matches("😀").count();
-
-                for _ in 0..found {
-                    if let Some(emoji) = emojis_iter.next() {
-                        let rep = match emoji {
-                            EmojiType::Discord(id) => format!("<:ee:{}>", id),
-                            EmojiType::Regular(e) => e.to_string(),
-                        };
-                        *line = line.replacen("😀", &rep, 1);
-                    }
-                }
-            });
+    (lines, true)
+}
 
-            lines
+/// Shortens `line` until it fits `width` followed by `ellipsis`, never
+/// cutting through an emoji. An ellipsis wider than `width` is clipped.
+fn ellipsize(
+    line: &str,
+    ellipsis: &str,
+    width: f32,
+    size: f32,
+    font: &Font,
+    draw_emojis: bool,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> String {
+    let parse = |text: &str| {
+        if draw_emojis {
+            imagetext::emoji::parse::parse_out_emojis(
+                text,
+                font.0.emoji_options.parse_shortcodes,
+                font.0.emoji_options.parse_discord_emojis,
+            )
         } else {
-            imagetext::wrap::text_wrap(
+            (text.to_string(), Vec::new())
+        }
+    };
+    let measure = |text: &str| {
+        if let Some(shaper) = shaper {
+            return layout_line(
                 text,
-                width,
                 &font.0,
                 scale(size),
-                wrap_style.unwrap_or(WrapStyle::Word).to_wrap_style(),
-                imagetext::measure::text_width,
+                draw_emojis,
+                spacing,
+                Some(shaper),
+                direction,
             )
+            .width;
         }
-    })
+
+        let width = if draw_emojis {
+            text_width_with_emojis(text, &font.0, scale(size))
+        } else {
+            imagetext::measure::text_width(text, &font.0, scale(size))
+        };
+        width + spacing.extra_width(text)
+    };
+
+    // emojis are single placeholder characters in the parsed text
+    let (line, line_emojis) = parse(line);
+    let (ellipsis, ellipsis_emojis) = parse(ellipsis);
+
+    let chars: Vec<char> = line.chars().collect();
+    let (fitted, ellipsis) = match fit_start(&chars, &ellipsis, width, measure) {
+        Some(fitted) => (fitted, ellipsis),
+        // not even the ellipsis fits, as much of it as does is kept alone
+        None => {
+            let chars: Vec<char> = ellipsis.chars().collect();
+            let clipped = fit_start(&chars, "", width, measure).unwrap_or_default();
+            (String::new(), clipped)
+        }
+    };
+
+    if !draw_emojis {
+        return fitted + &ellipsis;
+    }
+    let line_emojis: Vec<_> = line_emojis.iter().map(emoji_text).collect();
+    let ellipsis_emojis: Vec<_> = ellipsis_emojis.iter().map(emoji_text).collect();
+    join_ellipsis(&fitted, &line_emojis, &ellipsis, &ellipsis_emojis)
+}
+
+/// The longest start of `chars` that fits `width` followed by `suffix`, or
+/// `None` when not even the suffix fits. Widths grow with the kept
+/// characters, so the cut is searched like [`fit_size`] searches sizes.
+fn fit_start(
+    chars: &[char],
+    suffix: &str,
+    width: f32,
+    measure: impl Fn(&str) -> f32,
+) -> Option<String> {
+    let start = |kept: usize| {
+        let text: String = chars[..kept].iter().collect();
+        text.trim_end().to_string()
+    };
+    let fits = |kept: usize| measure(&(start(kept) + suffix)) <= width;
+    if !fits(0) {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let kept = low + (high - low).div_ceil(2);
+        if fits(kept) {
+            low = kept;
+        } else {
+            high = kept - 1;
+        }
+    }
+
+    Some(start(low))
+}
+
+/// Ends the kept start of a parsed line with a parsed ellipsis, putting the
+/// emojis parsed out of each back in place of their placeholders. Emojis
+/// cut off the end of the line are dropped.
+fn join_ellipsis(
+    fitted: &str,
+    line_emojis: &[String],
+    ellipsis: &str,
+    ellipsis_emojis: &[String],
+) -> String {
+    let kept = fitted.matches(EMOJI_PLACEHOLDER).count();
+    let mut emojis = line_emojis[..kept.min(line_emojis.len())]
+        .iter()
+        .chain(ellipsis_emojis);
+
+    fitted
+        .chars()
+        .chain(ellipsis.chars())
+        .map(|c| match c {
+            EMOJI_PLACEHOLDER => emojis.next().cloned().unwrap_or_default(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+pub fn wrap_lines(
+    text: &str,
+    width: i32,
+    size: f32,
+    font: &Font,
+    draw_emojis: Option<bool>,
+    wrap_style: Option<WrapStyle>,
+    spacing: Spacing,
+    shaper: Option<&Shaper>,
+    direction: Direction,
+) -> Vec<String> {
+    // imagetext measures with plain functions, spaced and shaped text is
+    // wrapped as it's laid out instead
+    if shaper.is_some() || !spacing.is_none() {
+        return wrap_text(
+            text,
+            width as f32,
+            &font.0,
+            scale(size),
+            draw_emojis.unwrap_or(false),
+            wrap_style.unwrap_or(WrapStyle::Word),
+            spacing,
+            shaper,
+            direction,
+        );
+    }
+
+    if draw_emojis.unwrap_or(false) {
+        let (text, emojis) = imagetext::emoji::parse::parse_out_emojis(
+            text,
+            font.0.emoji_options.parse_shortcodes,
+            font.0.emoji_options.parse_discord_emojis,
+        );
+
+        let mut lines = imagetext::wrap::text_wrap(
+            &text,
+            width,
+            &font.0,
+            scale(size),
+            wrap_style.unwrap_or(WrapStyle::Word).to_wrap_style(),
+            text_width_with_emojis,
+        );
+
+        let mut emojis_iter = emojis.iter();
+        lines.iter_mut().for_each(|line| {
+            let found = line.matches("😀").count();
+
+            for _ in 0..found {
+                if let Some(emoji) = emojis_iter.next() {
+                    *line = line.replacen("😀", &emoji_text(emoji), 1);
+                }
+            }
+        });
+
+        lines
+    } else {
+        imagetext::wrap::text_wrap(
+            text,
+            width,
+            &font.0,
+            scale(size),
+            wrap_style.unwrap_or(WrapStyle::Word).to_wrap_style(),
+            imagetext::measure::text_width,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipsis_emojis_follow_the_kept_text() {
+        let texts = |emojis: &[&str]| emojis.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        let line = texts(&[":a:", ":b:"]);
+        let ellipsis = texts(&[":fire:"]);
+
+        // both of the line's emojis were cut off
+        assert_eq!(join_ellipsis("", &line, "…😀", &ellipsis), "…:fire:");
+        assert_eq!(join_ellipsis("x😀", &line, "…😀", &ellipsis), "x:a:…:fire:");
+        assert_eq!(
+            join_ellipsis("😀y😀", &line, "…😀", &ellipsis),
+            ":a:y:b:…:fire:"
+        );
+        assert_eq!(join_ellipsis("x😀", &line, "...", &[]), "x:a:...");
+    }
+
+    #[test]
+    fn fit_start_keeps_the_longest_fitting_start() {
+        let chars: Vec<char> = "hello world".chars().collect();
+        let measure = |text: &str| text.chars().count() as f32;
+
+        assert_eq!(
+            fit_start(&chars, "…", 12.0, measure).as_deref(),
+            Some("hello world")
+        );
+        assert_eq!(
+            fit_start(&chars, "…", 8.0, measure).as_deref(),
+            Some("hello w")
+        );
+        // trailing spaces are trimmed before the suffix
+        assert_eq!(
+            fit_start(&chars, "…", 6.0, measure).as_deref(),
+            Some("hello")
+        );
+        assert_eq!(fit_start(&chars, "…", 1.0, measure).as_deref(), Some(""));
+        assert_eq!(fit_start(&chars, "…", 0.5, measure), None);
+    }
+
+    #[test]
+    fn ellipses_wider_than_the_width_are_clipped() {
+        let font = Font(
+            crate::testing::superfont(),
+            Features::default(),
+            crate::font::Variations::default(),
+        );
+        let shaper = Shaper::new(Some(true), &font.1, None);
+        let spacing = Spacing::default();
+        let width = |text: &str| {
+            layout_line(
+                text,
+                &font.0,
+                scale(20.0),
+                false,
+                spacing,
+                shaper.as_ref(),
+                Direction::Auto,
+            )
+            .width
+        };
+        let ellipsize = |width: f32| {
+            ellipsize(
+                "hello",
+                "...",
+                width,
+                20.0,
+                &font,
+                false,
+                spacing,
+                shaper.as_ref(),
+                Direction::Auto,
+            )
+        };
+
+        assert_eq!(ellipsize(width("he...")), "he...");
+        assert_eq!(ellipsize(width("...")), "...");
+        assert_eq!(ellipsize(width("..")), "..");
+        assert_eq!(ellipsize(0.0), "");
+    }
+
+    #[test]
+    fn fit_size_finds_the_largest_fitting_size() {
+        let (size, fitted) = fit_size(1.0, 100.0, |size| (size <= 42.5, size));
+        assert!((42.4..=42.5).contains(&size), "{}", size);
+        assert_eq!(size, fitted);
+
+        assert_eq!(fit_size(1.0, 100.0, |size| (true, size)), (100.0, 100.0));
+        // nothing fits, the smallest size is used anyway
+        assert_eq!(fit_size(5.0, 100.0, |size| (false, size)), (5.0, 5.0));
+    }
 }